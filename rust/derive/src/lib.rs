@@ -0,0 +1,691 @@
+//! `#[derive(Beve)]`: implements `beve::BeveWrite` and `beve::BeveRead`.
+//!
+//! Structs with named fields are written as string keyed objects, tuple
+//! structs as generic arrays (or as their only field), unit structs as null
+//! and enums as a type tag with the variant index followed by the variant's
+//! content, in the same layout as the serde serializer. With
+//! `Writer::set_struct_as_array` structs are written as generic arrays of
+//! their field values instead; both forms are read.
+//!
+//! Field attributes:
+//!
+//! - `#[beve(rename = "name")]` uses `name` as the key,
+//! - `#[beve(skip)]` neither writes nor reads the field, which is filled
+//!   with `Default::default()`,
+//! - `#[beve(default)]` fills the field with `Default::default()` when its
+//!   key is missing,
+//! - `#[beve(flatten)]` writes the members of a struct field into the
+//!   enclosing object.
+//!
+//! Variants accept `#[beve(rename = "name")]`, the name a unit variant may
+//! also be read from.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Ident, Index, LitStr, Type,
+};
+
+#[proc_macro_derive(Beve, attributes(beve))]
+pub fn derive_beve(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    skip: bool,
+    default: bool,
+    flatten: bool,
+}
+
+fn parse_attrs(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
+    let mut parsed = FieldAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("beve")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let name: LitStr = meta.value()?.parse()?;
+                parsed.rename = Some(name.value());
+            } else if meta.path.is_ident("skip") {
+                parsed.skip = true;
+            } else if meta.path.is_ident("default") {
+                parsed.default = true;
+            } else if meta.path.is_ident("flatten") {
+                parsed.flatten = true;
+            } else {
+                return Err(meta.error("expected `rename`, `skip`, `default` or `flatten`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// A named field of a struct or struct variant.
+struct Field {
+    ident: Ident,
+    ty: Type,
+    key: String,
+    attrs: FieldAttrs,
+}
+
+fn named_fields(fields: &syn::FieldsNamed, allow_flatten: bool) -> syn::Result<Vec<Field>> {
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let attrs = parse_attrs(&field.attrs)?;
+            if attrs.flatten && !allow_flatten {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "`flatten` is only supported on the fields of structs",
+                ));
+            }
+            if attrs.flatten && (attrs.rename.is_some() || attrs.default || attrs.skip) {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "`flatten` cannot be combined with other attributes",
+                ));
+            }
+            let ident = field.ident.clone().expect("named field");
+            let key = attrs
+                .rename
+                .clone()
+                .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+            Ok(Field {
+                ident,
+                ty: field.ty.clone(),
+                key,
+                attrs,
+            })
+        })
+        .collect()
+}
+
+fn check_unnamed(fields: &syn::FieldsUnnamed) -> syn::Result<()> {
+    for field in &fields.unnamed {
+        let attrs = parse_attrs(&field.attrs)?;
+        if attrs.rename.is_some() || attrs.skip || attrs.default || attrs.flatten {
+            return Err(syn::Error::new(
+                field.span(),
+                "attributes are only supported on named fields",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Adds `bound` to every type parameter.
+fn with_bound(generics: &Generics, bound: syn::Path) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let write_generics = with_bound(&input.generics, parse_quote!(::beve::BeveWrite));
+    let read_generics = with_bound(&input.generics, parse_quote!(::beve::BeveRead));
+
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let fields = named_fields(fields, true)?;
+                Ok(expand_object(
+                    &input,
+                    &fields,
+                    &write_generics,
+                    &read_generics,
+                ))
+            }
+            Fields::Unnamed(fields) => {
+                check_unnamed(fields)?;
+                Ok(expand_tuple_struct(
+                    &input,
+                    fields.unnamed.len(),
+                    &write_generics,
+                    &read_generics,
+                ))
+            }
+            Fields::Unit => Ok(expand_unit_struct(&input, &write_generics, &read_generics)),
+        },
+        Data::Enum(data) => expand_enum(&input, data, &write_generics, &read_generics),
+        Data::Union(_) => Err(syn::Error::new(
+            Span::call_site(),
+            "`Beve` cannot be derived for unions",
+        )),
+    }
+}
+
+/// Structs with named fields: string keyed objects.
+fn expand_object(
+    input: &DeriveInput,
+    fields: &[Field],
+    write_generics: &Generics,
+    read_generics: &Generics,
+) -> TokenStream2 {
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let written: Vec<&Field> = fields.iter().filter(|f| !f.attrs.skip).collect();
+    let counts = written.iter().map(|f| {
+        let ident = &f.ident;
+        if f.attrs.flatten {
+            quote!(::beve::__private::WriteFields::field_count(&self.#ident))
+        } else {
+            quote!(1)
+        }
+    });
+    let writes = written.iter().map(|f| {
+        let ident = &f.ident;
+        let key = &f.key;
+        if f.attrs.flatten {
+            quote!(::beve::__private::WriteFields::write_fields(&self.#ident, writer)?;)
+        } else {
+            quote!(::beve::__private::write_field(writer, #key, &self.#ident)?;)
+        }
+    });
+
+    let read: Vec<&Field> = fields.iter().filter(|f| !f.attrs.skip).collect();
+    let slots = read.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        if f.attrs.flatten {
+            quote!(#ident: <#ty as ::beve::__private::ReadFields>::Fields)
+        } else {
+            quote!(#ident: ::core::option::Option<#ty>)
+        }
+    });
+    let slot_idents: Vec<&Ident> = read.iter().map(|f| &f.ident).collect();
+    let arms = read.iter().filter(|f| !f.attrs.flatten).map(|f| {
+        let ident = &f.ident;
+        let key = &f.key;
+        quote! {
+            #key => {
+                fields.#ident = ::core::option::Option::Some(
+                    ::beve::__private::read_field(reader, #key)?,
+                );
+                ::core::result::Result::Ok(true)
+            }
+        }
+    });
+    let flattened: Vec<TokenStream2> = read
+        .iter()
+        .filter(|f| f.attrs.flatten)
+        .map(|f| {
+            let ident = &f.ident;
+            let ty = &f.ty;
+            quote!(<#ty as ::beve::__private::ReadFields>::read_field(&mut fields.#ident, key, reader)?)
+        })
+        .collect();
+    let fallback = if flattened.is_empty() {
+        quote!(::core::result::Result::Ok(false))
+    } else {
+        quote!(::core::result::Result::Ok(#(#flattened)||*))
+    };
+    let inits = fields.iter().map(|f| {
+        let ident = &f.ident;
+        init_field(f, quote!(fields.#ident), &format_ident!("offset"))
+    });
+
+    // Positions in the array form, where a flattened field takes as many
+    // elements as it has fields.
+    let mut plain = 0usize;
+    let mut nested: Vec<TokenStream2> = Vec::new();
+    let mut positions = Vec::new();
+    for f in &read {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        let offset = sum(plain, &nested);
+        if f.attrs.flatten {
+            let count = quote!(<#ty as ::beve::__private::ReadFields>::FIELD_COUNT);
+            positions.push(quote! {
+                if let ::core::option::Option::Some(index) =
+                    index.checked_sub(#offset).filter(|&index| index < #count)
+                {
+                    return <#ty as ::beve::__private::ReadFields>::read_index(
+                        &mut fields.#ident,
+                        index,
+                        reader,
+                    );
+                }
+            });
+            nested.push(count);
+        } else {
+            positions.push(quote! {
+                if index == #offset {
+                    fields.#ident = ::core::option::Option::Some(
+                        ::beve::BeveRead::read_beve(reader)?,
+                    );
+                    return ::core::result::Result::Ok(true);
+                }
+            });
+            plain += 1;
+        }
+    }
+    let field_count = sum(plain, &nested);
+
+    let (write_impl, _, write_where) = write_generics.split_for_impl();
+    let (read_impl, _, read_where) = read_generics.split_for_impl();
+
+    quote! {
+        const _: () = {
+            #[automatically_derived]
+            impl #write_impl ::beve::BeveWrite for #name #ty_generics #write_where {
+                fn write_beve(
+                    &self,
+                    writer: &mut ::beve::Writer<'_>,
+                ) -> ::core::result::Result<(), ::beve::Error> {
+                    ::beve::__private::write_object(self, writer)
+                }
+            }
+
+            #[automatically_derived]
+            impl #write_impl ::beve::__private::WriteFields for #name #ty_generics #write_where {
+                fn field_count(&self) -> usize {
+                    0 #(+ #counts)*
+                }
+
+                #[allow(unused_variables)]
+                fn write_fields(
+                    &self,
+                    writer: &mut ::beve::Writer<'_>,
+                ) -> ::core::result::Result<(), ::beve::Error> {
+                    #(#writes)*
+                    ::core::result::Result::Ok(())
+                }
+            }
+
+            #[doc(hidden)]
+            pub struct __Fields #read_impl #read_where {
+                #(#slots,)*
+                // Uses every generic parameter, even those only skipped
+                // fields mention.
+                __marker: ::core::marker::PhantomData<fn() -> #name #ty_generics>,
+            }
+
+            impl #read_impl ::core::default::Default for __Fields #ty_generics #read_where {
+                fn default() -> Self {
+                    __Fields {
+                        #(#slot_idents: ::core::default::Default::default(),)*
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl #read_impl ::beve::__private::ReadFields for #name #ty_generics #read_where {
+                type Fields = __Fields #ty_generics;
+
+                const FIELD_COUNT: usize = #field_count;
+
+                #[allow(unused_variables)]
+                fn read_field(
+                    fields: &mut Self::Fields,
+                    key: &str,
+                    reader: &mut ::beve::Reader<'_>,
+                ) -> ::core::result::Result<bool, ::beve::Error> {
+                    match key {
+                        #(#arms)*
+                        _ => #fallback,
+                    }
+                }
+
+                #[allow(unused_variables)]
+                fn read_index(
+                    fields: &mut Self::Fields,
+                    index: usize,
+                    reader: &mut ::beve::Reader<'_>,
+                ) -> ::core::result::Result<bool, ::beve::Error> {
+                    #(#positions)*
+                    ::core::result::Result::Ok(false)
+                }
+
+                #[allow(unused_variables)]
+                fn finish(
+                    fields: Self::Fields,
+                    offset: usize,
+                ) -> ::core::result::Result<Self, ::beve::Error> {
+                    ::core::result::Result::Ok(#name {
+                        #(#inits,)*
+                    })
+                }
+            }
+
+            #[automatically_derived]
+            impl #read_impl ::beve::BeveRead for #name #ty_generics #read_where {
+                fn read_beve(
+                    reader: &mut ::beve::Reader<'_>,
+                ) -> ::core::result::Result<Self, ::beve::Error> {
+                    ::beve::__private::read_object(reader)
+                }
+            }
+        };
+    }
+}
+
+/// `plain + nested[0] + nested[1] + ...`, without adding zero.
+fn sum(plain: usize, nested: &[TokenStream2]) -> TokenStream2 {
+    match (plain, nested) {
+        (plain, []) => quote!(#plain),
+        (0, nested) => quote!(#(#nested)+*),
+        (plain, nested) => quote!(#plain #(+ #nested)*),
+    }
+}
+
+/// The initializer of a field once every member has been read. `slot` holds
+/// what was read of the field and `offset` the offset of the value.
+fn init_field(f: &Field, slot: TokenStream2, offset: &Ident) -> TokenStream2 {
+    let ident = &f.ident;
+    let ty = &f.ty;
+    let key = &f.key;
+    if f.attrs.skip {
+        quote!(#ident: ::core::default::Default::default())
+    } else if f.attrs.flatten {
+        quote!(#ident: <#ty as ::beve::__private::ReadFields>::finish(#slot, #offset)?)
+    } else if f.attrs.default {
+        quote!(#ident: #slot.unwrap_or_default())
+    } else {
+        quote!(#ident: ::beve::__private::required(#slot, #key, #offset)?)
+    }
+}
+
+/// Tuple structs: the only field itself, or a generic array of the fields.
+fn expand_tuple_struct(
+    input: &DeriveInput,
+    len: usize,
+    write_generics: &Generics,
+    read_generics: &Generics,
+) -> TokenStream2 {
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let (write_impl, _, write_where) = write_generics.split_for_impl();
+    let (read_impl, _, read_where) = read_generics.split_for_impl();
+
+    let (write_body, read_body) = if len == 1 {
+        (
+            quote!(::beve::BeveWrite::write_beve(&self.0, writer)),
+            quote!(::core::result::Result::Ok(#name(::beve::BeveRead::read_beve(reader)?))),
+        )
+    } else {
+        let indices: Vec<Index> = (0..len).map(Index::from).collect();
+        let positions = 0..len;
+        (
+            quote! {
+                ::beve::__private::write_array_header(writer, #len)?;
+                #(::beve::__private::write_element(writer, #indices, &self.#indices)?;)*
+                ::core::result::Result::Ok(())
+            },
+            quote! {
+                ::beve::__private::read_array(reader, #len, |reader| {
+                    ::core::result::Result::Ok(#name(
+                        #(::beve::__private::read_element(reader, #positions)?,)*
+                    ))
+                })
+            },
+        )
+    };
+
+    quote! {
+        #[automatically_derived]
+        impl #write_impl ::beve::BeveWrite for #name #ty_generics #write_where {
+            fn write_beve(
+                &self,
+                writer: &mut ::beve::Writer<'_>,
+            ) -> ::core::result::Result<(), ::beve::Error> {
+                #write_body
+            }
+        }
+
+        #[automatically_derived]
+        impl #read_impl ::beve::BeveRead for #name #ty_generics #read_where {
+            fn read_beve(
+                reader: &mut ::beve::Reader<'_>,
+            ) -> ::core::result::Result<Self, ::beve::Error> {
+                #read_body
+            }
+        }
+    }
+}
+
+/// Unit structs: null.
+fn expand_unit_struct(
+    input: &DeriveInput,
+    write_generics: &Generics,
+    read_generics: &Generics,
+) -> TokenStream2 {
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let (write_impl, _, write_where) = write_generics.split_for_impl();
+    let (read_impl, _, read_where) = read_generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #write_impl ::beve::BeveWrite for #name #ty_generics #write_where {
+            fn write_beve(
+                &self,
+                writer: &mut ::beve::Writer<'_>,
+            ) -> ::core::result::Result<(), ::beve::Error> {
+                ::beve::__private::write_null(writer)
+            }
+        }
+
+        #[automatically_derived]
+        impl #read_impl ::beve::BeveRead for #name #ty_generics #read_where {
+            fn read_beve(
+                reader: &mut ::beve::Reader<'_>,
+            ) -> ::core::result::Result<Self, ::beve::Error> {
+                ::beve::__private::read_null(reader)?;
+                ::core::result::Result::Ok(#name)
+            }
+        }
+    }
+}
+
+/// Enums: a type tag holding the variant index, then the variant as a unit,
+/// newtype, tuple or struct would be written.
+fn expand_enum(
+    input: &DeriveInput,
+    data: &syn::DataEnum,
+    write_generics: &Generics,
+    read_generics: &Generics,
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let (write_impl, _, write_where) = write_generics.split_for_impl();
+    let (read_impl, _, read_where) = read_generics.split_for_impl();
+
+    let mut write_arms = Vec::new();
+    let mut read_arms = Vec::new();
+    for (index, variant) in data.variants.iter().enumerate() {
+        let attrs = parse_attrs(&variant.attrs)?;
+        if attrs.skip || attrs.default || attrs.flatten {
+            return Err(syn::Error::new(
+                variant.span(),
+                "variants only support `rename`",
+            ));
+        }
+        let ident = &variant.ident;
+        let variant_name = attrs.rename.unwrap_or_else(|| ident.to_string());
+        let index = index as u64;
+
+        match &variant.fields {
+            Fields::Unit => {
+                write_arms.push(quote! {
+                    #name::#ident => {
+                        ::beve::__private::write_tag(__writer, #index)?;
+                        ::beve::__private::write_null(__writer)
+                    }
+                });
+                read_arms.push(quote! {
+                    ::beve::__private::Tag::Index(#index) => {
+                        ::beve::__private::read_null(__reader)?;
+                        ::core::result::Result::Ok(#name::#ident)
+                    }
+                    ::beve::__private::Tag::Name(#variant_name) => {
+                        ::core::result::Result::Ok(#name::#ident)
+                    }
+                });
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                check_unnamed(fields)?;
+                write_arms.push(quote! {
+                    #name::#ident(__value) => {
+                        ::beve::__private::write_tag(__writer, #index)?;
+                        ::beve::BeveWrite::write_beve(__value, __writer)
+                    }
+                });
+                read_arms.push(quote! {
+                    ::beve::__private::Tag::Index(#index) => {
+                        ::core::result::Result::Ok(#name::#ident(
+                            ::beve::BeveRead::read_beve(__reader)?,
+                        ))
+                    }
+                });
+            }
+            Fields::Unnamed(fields) => {
+                check_unnamed(fields)?;
+                let len = fields.unnamed.len();
+                let bindings: Vec<Ident> =
+                    (0..len).map(|i| format_ident!("__field{}", i)).collect();
+                let positions = 0..len;
+                let write_positions = 0..len;
+                write_arms.push(quote! {
+                    #name::#ident(#(#bindings),*) => {
+                        ::beve::__private::write_tag(__writer, #index)?;
+                        ::beve::__private::write_array_header(__writer, #len)?;
+                        #(::beve::__private::write_element(__writer, #write_positions, #bindings)?;)*
+                        ::core::result::Result::Ok(())
+                    }
+                });
+                read_arms.push(quote! {
+                    ::beve::__private::Tag::Index(#index) => {
+                        ::beve::__private::read_array(__reader, #len, |__reader| {
+                            ::core::result::Result::Ok(#name::#ident(
+                                #(::beve::__private::read_element(__reader, #positions)?,)*
+                            ))
+                        })
+                    }
+                });
+            }
+            Fields::Named(fields) => {
+                let fields = named_fields(fields, false)?;
+                let written: Vec<&Field> = fields.iter().filter(|f| !f.attrs.skip).collect();
+                // Bound and read under prefixed names, so that fields named
+                // like the locals of the generated code don't clash with them.
+                let binding = |f: &Field| format_ident!("__field_{}", f.ident);
+                let idents = written.iter().map(|f| &f.ident);
+                let bindings: Vec<Ident> = written.iter().map(|f| binding(f)).collect();
+                let count = written.len();
+                let writes = written.iter().map(|f| {
+                    let binding = binding(f);
+                    let key = &f.key;
+                    quote!(::beve::__private::write_field(__writer, #key, #binding)?;)
+                });
+                write_arms.push(quote! {
+                    #name::#ident { #(#idents: #bindings,)* .. } => {
+                        ::beve::__private::write_tag(__writer, #index)?;
+                        ::beve::__private::write_object_header(__writer, #count)?;
+                        #(#writes)*
+                        ::core::result::Result::Ok(())
+                    }
+                });
+
+                let slots = written.iter().map(|f| {
+                    let binding = binding(f);
+                    let ty = &f.ty;
+                    quote!(let mut #binding: ::core::option::Option<#ty> = ::core::option::Option::None;)
+                });
+                let arms = written.iter().map(|f| {
+                    let binding = binding(f);
+                    let key = &f.key;
+                    quote! {
+                        #key => {
+                            #binding = ::core::option::Option::Some(
+                                ::beve::__private::read_field(__reader, #key)?,
+                            );
+                            ::core::result::Result::Ok(true)
+                        }
+                    }
+                });
+                let elements = written.iter().enumerate().map(|(position, f)| {
+                    let binding = binding(f);
+                    quote! {
+                        #position => {
+                            #binding = ::core::option::Option::Some(
+                                ::beve::BeveRead::read_beve(__reader)?,
+                            );
+                            ::core::result::Result::Ok(true)
+                        }
+                    }
+                });
+                let offset = format_ident!("__offset");
+                let inits = fields
+                    .iter()
+                    .map(|f| init_field(f, binding(f).into_token_stream(), &offset));
+                read_arms.push(quote! {
+                    ::beve::__private::Tag::Index(#index) => {
+                        #(#slots)*
+                        if ::beve::__private::is_array(__reader)? {
+                            ::beve::__private::read_elements(__reader, |__index, __reader| match __index {
+                                #(#elements)*
+                                _ => ::core::result::Result::Ok(false),
+                            })?;
+                        } else {
+                            ::beve::__private::read_members(__reader, |__key, __reader| match __key {
+                                #(#arms)*
+                                _ => ::core::result::Result::Ok(false),
+                            })?;
+                        }
+                        ::core::result::Result::Ok(#name::#ident {
+                            #(#inits,)*
+                        })
+                    }
+                });
+            }
+        }
+    }
+
+    let write_body = if write_arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match self {
+                #(#write_arms)*
+            }
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #write_impl ::beve::BeveWrite for #name #ty_generics #write_where {
+            fn write_beve(
+                &self,
+                __writer: &mut ::beve::Writer<'_>,
+            ) -> ::core::result::Result<(), ::beve::Error> {
+                #write_body
+            }
+        }
+
+        #[automatically_derived]
+        impl #read_impl ::beve::BeveRead for #name #ty_generics #read_where {
+            fn read_beve(
+                __reader: &mut ::beve::Reader<'_>,
+            ) -> ::core::result::Result<Self, ::beve::Error> {
+                let __offset = __reader.position();
+                ::beve::__private::read_variant(__reader, |__tag, __reader| match __tag {
+                    #(#read_arms)*
+                    _ => ::core::result::Result::Err(::beve::__private::unknown_variant(__offset)),
+                })
+            }
+        }
+    })
+}
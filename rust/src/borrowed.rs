@@ -0,0 +1,609 @@
+// Zero-copy reading: values that borrow their strings and typed array data
+// from the input.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::error::Error;
+use crate::ext::{Element, Layout};
+use crate::header::{self, BOOL_OR_STRING, FLOAT, SIGNED, UNSIGNED};
+use crate::reader::Reader;
+use crate::value::{Key, TypedArray, Value, BF16, F128, F16};
+
+/// A view of the DATA of a numeric typed array.
+#[derive(Clone, Copy)]
+pub struct Numbers<'a, T> {
+    bytes: &'a [u8],
+    element: PhantomData<T>,
+}
+
+impl<'a, T: Element> Numbers<'a, T> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Numbers {
+            bytes,
+            element: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() / std::mem::size_of::<T>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The little-endian DATA.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The elements in place, when the platform is little-endian and the data
+    /// happens to be aligned for `T`.
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        if cfg!(target_endian = "little") {
+            T::cast_slice(self.bytes)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        let width = std::mem::size_of::<T>();
+        let start = index.checked_mul(width)?;
+        let end = start.checked_add(width)?;
+        self.bytes.get(start..end).map(T::from_le_bytes)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = T> + 'a {
+        self.bytes
+            .chunks_exact(std::mem::size_of::<T>())
+            .map(T::from_le_bytes)
+    }
+
+    /// Copies the elements, in one go on little-endian platforms.
+    pub fn to_vec(&self) -> Vec<T> {
+        if let Some(slice) = self.as_slice() {
+            return slice.to_vec();
+        }
+        if cfg!(target_endian = "little") {
+            // Unaligned data is copied into place as bytes.
+            let mut values = vec![T::zeroed(); self.len()];
+            bytemuck::cast_slice_mut(&mut values).copy_from_slice(self.bytes);
+            values
+        } else {
+            self.iter().collect()
+        }
+    }
+}
+
+impl<T: Element + fmt::Debug> fmt::Debug for Numbers<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> PartialEq for Numbers<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+/// A view of the DATA of a boolean typed array, packed eight to a byte.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Bits<'a> {
+    bytes: &'a [u8],
+    len: usize,
+}
+
+impl<'a> Bits<'a> {
+    /// The first `len` bits of `bytes`.
+    pub(crate) fn new(bytes: &'a [u8], len: usize) -> Self {
+        Bits { bytes, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = bool> + 'a {
+        let bytes = self.bytes;
+        (0..self.len).map(move |i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+    }
+}
+
+impl fmt::Debug for Bits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A view of the DATA of a string typed array. The strings were checked to
+/// be UTF-8 when the array was read.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Strings<'a> {
+    bytes: &'a [u8],
+    len: usize,
+}
+
+impl<'a> Strings<'a> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &'a str> + 'a {
+        let mut reader = Reader::new(self.bytes);
+        (0..self.len).map(move |_| {
+            reader
+                .read_str()
+                .expect("strings are checked when the array is read")
+        })
+    }
+}
+
+impl fmt::Debug for Strings<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A typed array whose DATA is borrowed from the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypedArrayRef<'a> {
+    Bool(Bits<'a>),
+    String(Strings<'a>),
+    I8(Numbers<'a, i8>),
+    I16(Numbers<'a, i16>),
+    I32(Numbers<'a, i32>),
+    I64(Numbers<'a, i64>),
+    I128(Numbers<'a, i128>),
+    U8(Numbers<'a, u8>),
+    U16(Numbers<'a, u16>),
+    U32(Numbers<'a, u32>),
+    U64(Numbers<'a, u64>),
+    U128(Numbers<'a, u128>),
+    BF16(Numbers<'a, BF16>),
+    F16(Numbers<'a, F16>),
+    F32(Numbers<'a, f32>),
+    F64(Numbers<'a, f64>),
+    F128(Numbers<'a, F128>),
+}
+
+macro_rules! typed_array_ref_dispatch {
+    ($array:expr, $v:ident => $body:expr) => {
+        match $array {
+            TypedArrayRef::Bool($v) => $body,
+            TypedArrayRef::String($v) => $body,
+            TypedArrayRef::I8($v) => $body,
+            TypedArrayRef::I16($v) => $body,
+            TypedArrayRef::I32($v) => $body,
+            TypedArrayRef::I64($v) => $body,
+            TypedArrayRef::I128($v) => $body,
+            TypedArrayRef::U8($v) => $body,
+            TypedArrayRef::U16($v) => $body,
+            TypedArrayRef::U32($v) => $body,
+            TypedArrayRef::U64($v) => $body,
+            TypedArrayRef::U128($v) => $body,
+            TypedArrayRef::BF16($v) => $body,
+            TypedArrayRef::F16($v) => $body,
+            TypedArrayRef::F32($v) => $body,
+            TypedArrayRef::F64($v) => $body,
+            TypedArrayRef::F128($v) => $body,
+        }
+    };
+}
+
+impl TypedArrayRef<'_> {
+    pub fn len(&self) -> usize {
+        typed_array_ref_dispatch!(self, v => v.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies the elements into an owned typed array.
+    pub fn to_typed_array(&self) -> TypedArray {
+        match self {
+            TypedArrayRef::Bool(v) => TypedArray::Bool(v.iter().collect()),
+            TypedArrayRef::String(v) => TypedArray::String(v.iter().map(String::from).collect()),
+            TypedArrayRef::I8(v) => TypedArray::I8(v.to_vec()),
+            TypedArrayRef::I16(v) => TypedArray::I16(v.to_vec()),
+            TypedArrayRef::I32(v) => TypedArray::I32(v.to_vec()),
+            TypedArrayRef::I64(v) => TypedArray::I64(v.to_vec()),
+            TypedArrayRef::I128(v) => TypedArray::I128(v.to_vec()),
+            TypedArrayRef::U8(v) => TypedArray::U8(v.to_vec()),
+            TypedArrayRef::U16(v) => TypedArray::U16(v.to_vec()),
+            TypedArrayRef::U32(v) => TypedArray::U32(v.to_vec()),
+            TypedArrayRef::U64(v) => TypedArray::U64(v.to_vec()),
+            TypedArrayRef::U128(v) => TypedArray::U128(v.to_vec()),
+            TypedArrayRef::BF16(v) => TypedArray::BF16(v.to_vec()),
+            TypedArrayRef::F16(v) => TypedArray::F16(v.to_vec()),
+            TypedArrayRef::F32(v) => TypedArray::F32(v.to_vec()),
+            TypedArrayRef::F64(v) => TypedArray::F64(v.to_vec()),
+            TypedArrayRef::F128(v) => TypedArray::F128(v.to_vec()),
+        }
+    }
+}
+
+/// An object key borrowed from the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyRef<'a> {
+    String(&'a str),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+}
+
+impl KeyRef<'_> {
+    pub fn to_key(&self) -> Key {
+        match *self {
+            KeyRef::String(s) => Key::String(s.to_string()),
+            KeyRef::I8(v) => Key::I8(v),
+            KeyRef::I16(v) => Key::I16(v),
+            KeyRef::I32(v) => Key::I32(v),
+            KeyRef::I64(v) => Key::I64(v),
+            KeyRef::I128(v) => Key::I128(v),
+            KeyRef::U8(v) => Key::U8(v),
+            KeyRef::U16(v) => Key::U16(v),
+            KeyRef::U32(v) => Key::U32(v),
+            KeyRef::U64(v) => Key::U64(v),
+            KeyRef::U128(v) => Key::U128(v),
+        }
+    }
+}
+
+impl fmt::Display for KeyRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyRef::String(s) => f.write_str(s),
+            key => key.to_key().fmt(f),
+        }
+    }
+}
+
+/// A value that borrows its strings, keys and typed array data from the
+/// input, as read by [`Reader::read_value_ref`]. Only objects and generic
+/// arrays allocate, for the list of their members.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    Null,
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    BF16(BF16),
+    F16(F16),
+    F32(f32),
+    F64(f64),
+    F128(F128),
+    String(&'a str),
+    /// Object members in the order they were written.
+    Object(Vec<(KeyRef<'a>, ValueRef<'a>)>),
+    TypedArray(TypedArrayRef<'a>),
+    Array(Vec<ValueRef<'a>>),
+    /// A value tagged with the index of its alternative.
+    Variant {
+        index: u64,
+        value: Box<ValueRef<'a>>,
+    },
+    /// A matrix with its elements in one flat typed array.
+    Matrix {
+        layout: Layout,
+        extents: Vec<u64>,
+        data: TypedArrayRef<'a>,
+    },
+    /// A complex number: a typed array holding its real and imaginary parts.
+    Complex(TypedArrayRef<'a>),
+    /// An array of complex numbers, with real and imaginary parts interleaved.
+    ComplexArray(TypedArrayRef<'a>),
+}
+
+impl<'a> ValueRef<'a> {
+    pub fn is_null(&self) -> bool {
+        matches!(self, ValueRef::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ValueRef::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            ValueRef::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[ValueRef<'a>]> {
+        match self {
+            ValueRef::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn as_typed_array(&self) -> Option<&TypedArrayRef<'a>> {
+        match self {
+            ValueRef::TypedArray(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(KeyRef<'a>, ValueRef<'a>)]> {
+        match self {
+            ValueRef::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Looks up a member of a string keyed object.
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        self.as_object()?
+            .iter()
+            .find(|(k, _)| *k == KeyRef::String(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Looks up an element of a generic array.
+    pub fn get_index(&self, index: usize) -> Option<&ValueRef<'a>> {
+        self.as_array()?.get(index)
+    }
+
+    /// Copies the value into an owned [`Value`].
+    pub fn to_value(&self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(v) => Value::Bool(*v),
+            ValueRef::I8(v) => Value::I8(*v),
+            ValueRef::I16(v) => Value::I16(*v),
+            ValueRef::I32(v) => Value::I32(*v),
+            ValueRef::I64(v) => Value::I64(*v),
+            ValueRef::I128(v) => Value::I128(*v),
+            ValueRef::U8(v) => Value::U8(*v),
+            ValueRef::U16(v) => Value::U16(*v),
+            ValueRef::U32(v) => Value::U32(*v),
+            ValueRef::U64(v) => Value::U64(*v),
+            ValueRef::U128(v) => Value::U128(*v),
+            ValueRef::BF16(v) => Value::BF16(*v),
+            ValueRef::F16(v) => Value::F16(*v),
+            ValueRef::F32(v) => Value::F32(*v),
+            ValueRef::F64(v) => Value::F64(*v),
+            ValueRef::F128(v) => Value::F128(*v),
+            ValueRef::String(s) => Value::String(s.to_string()),
+            ValueRef::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(k, v)| (k.to_key(), v.to_value()))
+                    .collect(),
+            ),
+            ValueRef::TypedArray(arr) => Value::TypedArray(arr.to_typed_array()),
+            ValueRef::Array(arr) => Value::Array(arr.iter().map(ValueRef::to_value).collect()),
+            ValueRef::Variant { index, value } => Value::Variant {
+                index: *index,
+                value: Box::new(value.to_value()),
+            },
+            ValueRef::Matrix {
+                layout,
+                extents,
+                data,
+            } => Value::Matrix {
+                layout: *layout,
+                extents: extents.clone(),
+                data: data.to_typed_array(),
+            },
+            ValueRef::Complex(parts) => Value::Complex(parts.to_typed_array()),
+            ValueRef::ComplexArray(parts) => Value::ComplexArray(parts.to_typed_array()),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(v: ValueRef<'_>) -> Self {
+        v.to_value()
+    }
+}
+
+impl<'a> Reader<'a> {
+    /// Reads one value without copying its strings or typed arrays. At the
+    /// top level any data delimiters in front of the value are skipped.
+    ///
+    /// Half precision numbers are never widened.
+    pub fn read_value_ref(&mut self) -> Result<ValueRef<'a>, Error> {
+        if self.depth == 0 {
+            while self.read_delimiter() {}
+        }
+        let start = self.cursor;
+        let header = self.read_uint8()?;
+
+        match header::value_type(header) {
+            header::NULL => {
+                if header & 0b00001000 != 0 {
+                    Ok(ValueRef::Bool(header & 0b11110000 != 0))
+                } else {
+                    Ok(ValueRef::Null)
+                }
+            }
+            header::NUMBER => match (header::number_type(header), header::byte_count(header)) {
+                // bfloat16 uses a BYTE COUNT of 1 although it takes 2 bytes.
+                (FLOAT, 1) => Ok(ValueRef::BF16(self.read_bf16()?)),
+                (FLOAT, 2) => Ok(ValueRef::F16(self.read_f16()?)),
+                (FLOAT, 4) => Ok(ValueRef::F32(self.read_float()?)),
+                (FLOAT, 8) => Ok(ValueRef::F64(self.read_double()?)),
+                (FLOAT, 16) => Ok(ValueRef::F128(self.read_f128()?)),
+                (SIGNED, 1) => Ok(ValueRef::I8(self.read_int8()?)),
+                (SIGNED, 2) => Ok(ValueRef::I16(self.read_int16()?)),
+                (SIGNED, 4) => Ok(ValueRef::I32(self.read_int32()?)),
+                (SIGNED, 8) => Ok(ValueRef::I64(self.read_int64()?)),
+                (SIGNED, 16) => Ok(ValueRef::I128(self.read_int128()?)),
+                (UNSIGNED, 1) => Ok(ValueRef::U8(self.read_uint8()?)),
+                (UNSIGNED, 2) => Ok(ValueRef::U16(self.read_uint16()?)),
+                (UNSIGNED, 4) => Ok(ValueRef::U32(self.read_uint32()?)),
+                (UNSIGNED, 8) => Ok(ValueRef::U64(self.read_uint64()?)),
+                (UNSIGNED, 16) => Ok(ValueRef::U128(self.read_uint128()?)),
+                (BOOL_OR_STRING, _) => Err(Error::invalid_header(start, header)),
+                _ => Err(Error::unsupported_type(start, header)),
+            },
+            header::STRING => Ok(ValueRef::String(self.read_str()?)),
+            header::OBJECT => {
+                let key_size = match (header::number_type(header), header::byte_count(header)) {
+                    (0, _) => 1,
+                    (SIGNED | UNSIGNED, n @ (1 | 2 | 4 | 8 | 16)) => n,
+                    (BOOL_OR_STRING, _) => return Err(Error::invalid_header(start, header)),
+                    _ => return Err(Error::unsupported_type(start, header)),
+                };
+                // Every member takes at least its key and a one byte HEADER.
+                let n = self.read_count(key_size + 1)?;
+
+                self.enter()?;
+                let mut members = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let key = self.read_key_ref(header)?;
+                    let value = self
+                        .read_value_ref()
+                        .map_err(|e| e.at_key(key.to_string()))?;
+                    members.push((key, value));
+                }
+                self.leave();
+
+                Ok(ValueRef::Object(members))
+            }
+            header::TYPED_ARRAY => Ok(ValueRef::TypedArray(self.read_typed_array_ref(header)?)),
+            header::GENERIC_ARRAY => {
+                let n = self.read_count(1)?;
+
+                self.enter()?;
+                let mut arr = Vec::with_capacity(n);
+                for i in 0..n {
+                    arr.push(self.read_value_ref().map_err(|e| e.at_index(i))?);
+                }
+                self.leave();
+
+                Ok(ValueRef::Array(arr))
+            }
+            header::EXTENSION => {
+                self.enter()?;
+                let value = self.read_extension_ref(header)?;
+                self.leave();
+                Ok(value)
+            }
+            _ => Err(Error::invalid_header(start, header)),
+        }
+    }
+
+    /// Reads one key of an object with the given HEADER.
+    pub(crate) fn read_key_ref(&mut self, header: u8) -> Result<KeyRef<'a>, Error> {
+        let key = match (header::number_type(header), header::byte_count(header)) {
+            (0, _) => KeyRef::String(self.read_str()?),
+            (SIGNED, 1) => KeyRef::I8(self.read_int8()?),
+            (SIGNED, 2) => KeyRef::I16(self.read_int16()?),
+            (SIGNED, 4) => KeyRef::I32(self.read_int32()?),
+            (SIGNED, 8) => KeyRef::I64(self.read_int64()?),
+            (SIGNED, 16) => KeyRef::I128(self.read_int128()?),
+            (UNSIGNED, 1) => KeyRef::U8(self.read_uint8()?),
+            (UNSIGNED, 2) => KeyRef::U16(self.read_uint16()?),
+            (UNSIGNED, 4) => KeyRef::U32(self.read_uint32()?),
+            (UNSIGNED, 8) => KeyRef::U64(self.read_uint64()?),
+            (UNSIGNED, 16) => KeyRef::U128(self.read_uint128()?),
+            _ => return Err(Error::unsupported_type(self.cursor, header)),
+        };
+        Ok(key)
+    }
+
+    /// Reads the SIZE and DATA of a typed array whose HEADER has already been
+    /// read.
+    pub(crate) fn read_typed_array_ref(&mut self, header: u8) -> Result<TypedArrayRef<'a>, Error> {
+        let start = self.cursor - 1;
+
+        if header::number_type(header) == BOOL_OR_STRING {
+            let is_string = (header & 0b00100000) >> 5;
+            if is_string != 0 {
+                let len = self.read_count(1)?;
+                let data_start = self.cursor;
+                for i in 0..len {
+                    self.read_str().map_err(|e| e.at_index(i))?;
+                }
+                Ok(TypedArrayRef::String(Strings {
+                    bytes: &self.buffer[data_start..self.cursor],
+                    len,
+                }))
+            } else {
+                let len = self.read_size()?;
+                let bytes = self.read_bytes(len.div_ceil(8))?;
+                Ok(TypedArrayRef::Bool(Bits { bytes, len }))
+            }
+        } else {
+            let n = self.read_size()?;
+            self.read_numbers_ref(header, start, n)
+        }
+    }
+
+    /// Reads `n` numbers of the type given by the numeric typed array HEADER
+    /// found at `at`.
+    pub(crate) fn read_numbers_ref(
+        &mut self,
+        header: u8,
+        at: usize,
+        n: usize,
+    ) -> Result<TypedArrayRef<'a>, Error> {
+        macro_rules! view {
+            ($variant:ident, $t:ty) => {{
+                let size = match n.checked_mul(std::mem::size_of::<$t>()) {
+                    Some(size) if size <= self.remaining() => size,
+                    _ => return Err(self.eof()),
+                };
+                Ok(TypedArrayRef::$variant(Numbers::new(
+                    self.read_bytes(size)?,
+                )))
+            }};
+        }
+
+        match (header::number_type(header), header::byte_count(header)) {
+            (FLOAT, 1) => view!(BF16, BF16),
+            (FLOAT, 2) => view!(F16, F16),
+            (FLOAT, 4) => view!(F32, f32),
+            (FLOAT, 8) => view!(F64, f64),
+            (FLOAT, 16) => view!(F128, F128),
+            (SIGNED, 1) => view!(I8, i8),
+            (SIGNED, 2) => view!(I16, i16),
+            (SIGNED, 4) => view!(I32, i32),
+            (SIGNED, 8) => view!(I64, i64),
+            (SIGNED, 16) => view!(I128, i128),
+            (UNSIGNED, 1) => view!(U8, u8),
+            (UNSIGNED, 2) => view!(U16, u16),
+            (UNSIGNED, 4) => view!(U32, u32),
+            (UNSIGNED, 8) => view!(U64, u64),
+            (UNSIGNED, 16) => view!(U128, u128),
+            (BOOL_OR_STRING, _) => Err(Error::invalid_header(at, header)),
+            _ => Err(Error::unsupported_type(at, header)),
+        }
+    }
+}
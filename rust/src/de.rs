@@ -0,0 +1,630 @@
+// Serde deserialization directly from BEVE bytes.
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeSeed, IgnoredAny, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::error::Error;
+use crate::header::{self, BOOL_OR_STRING, FLOAT, SIGNED, UNSIGNED};
+use crate::reader::Reader;
+
+/// Deserializes Rust values from BEVE without an intermediate [`Value`](crate::Value).
+///
+/// Structs are read from string keyed objects or, as written with
+/// [`Writer::set_struct_as_array`](crate::Writer::set_struct_as_array), from
+/// generic arrays of their fields in order.
+pub struct Deserializer<'de> {
+    reader: Reader<'de>,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn from_slice(bytes: &'de [u8]) -> Self {
+        Deserializer {
+            reader: Reader::new(bytes),
+        }
+    }
+
+    /// Starts reading at byte `cursor` of `bytes`, so that error offsets are
+    /// relative to the whole buffer.
+    pub(crate) fn at(bytes: &'de [u8], cursor: usize) -> Self {
+        let mut reader = Reader::new(bytes);
+        reader.cursor = cursor;
+        Deserializer { reader }
+    }
+
+    /// Byte offset of the next value to be read.
+    pub(crate) fn position(&self) -> usize {
+        self.reader.cursor
+    }
+
+    /// Whether reading needed more bytes than the input holds.
+    pub(crate) fn ran_short(&self) -> bool {
+        self.reader.ran_short()
+    }
+
+    /// Limits how deeply values may nest before deserializing fails with
+    /// [`Error::DepthLimit`].
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.reader.set_max_depth(max_depth);
+    }
+
+    /// Checks that the whole input has been consumed.
+    pub fn end(&self) -> Result<(), Error> {
+        if self.reader.cursor == self.reader.buffer.len() {
+            Ok(())
+        } else {
+            Err(Error::TrailingBytes {
+                offset: self.reader.cursor,
+            })
+        }
+    }
+}
+
+/// Deserializes an instance of `T` from BEVE bytes.
+pub fn from_slice<'a, T: de::Deserialize<'a>>(bytes: &'a [u8]) -> Result<T, Error> {
+    let mut deserializer = Deserializer::from_slice(bytes);
+    let value = T::deserialize(&mut deserializer).map_err(|e| e.or_offset(0))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+impl<'de> Deserializer<'de> {
+    /// The key of the object member whose key starts at `offset`, for error
+    /// paths. Keys are only decoded a second time when an error is reported.
+    fn key_at(&self, offset: usize, header: u8) -> String {
+        let mut reader = Reader::new(self.reader.buffer);
+        reader.cursor = offset;
+        reader
+            .read_key(header)
+            .map(|key| key.to_string())
+            .unwrap_or_default()
+    }
+
+    fn visit_number<V: Visitor<'de>>(&mut self, header: u8, visitor: V) -> Result<V::Value, Error> {
+        let num_type = header::number_type(header);
+        let byte_count = header::byte_count(header);
+        let reader = &mut self.reader;
+
+        match (num_type, byte_count) {
+            // Serde has no half precision types, so these are widened.
+            (FLOAT, 1) => visitor.visit_f32(reader.read_bf16()?.to_f32()),
+            (FLOAT, 2) => visitor.visit_f32(reader.read_f16()?.to_f32()),
+            (FLOAT, 4) => visitor.visit_f32(reader.read_float()?),
+            (FLOAT, 8) => visitor.visit_f64(reader.read_double()?),
+            // Nor a quadruple precision type, so these are the raw bytes.
+            (FLOAT, 16) => visitor.visit_borrowed_bytes(reader.read_bytes(16)?),
+            (SIGNED, 1) => visitor.visit_i8(reader.read_int8()?),
+            (SIGNED, 2) => visitor.visit_i16(reader.read_int16()?),
+            (SIGNED, 4) => visitor.visit_i32(reader.read_int32()?),
+            (SIGNED, 8) => visitor.visit_i64(reader.read_int64()?),
+            (SIGNED, 16) => visitor.visit_i128(reader.read_int128()?),
+            (UNSIGNED, 1) => visitor.visit_u8(reader.read_uint8()?),
+            (UNSIGNED, 2) => visitor.visit_u16(reader.read_uint16()?),
+            (UNSIGNED, 4) => visitor.visit_u32(reader.read_uint32()?),
+            (UNSIGNED, 8) => visitor.visit_u64(reader.read_uint64()?),
+            (UNSIGNED, 16) => visitor.visit_u128(reader.read_uint128()?),
+            (BOOL_OR_STRING, _) => Err(Error::invalid_header(reader.cursor - 1, header)),
+            _ => Err(Error::unsupported_type(reader.cursor - 1, header)),
+        }
+    }
+
+    fn visit_extension<V: Visitor<'de>>(
+        &mut self,
+        header: u8,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let start = self.reader.cursor - 1;
+        match header::extension(header) {
+            0 => Err(Error::type_mismatch(
+                start,
+                "a value rather than a data delimiter",
+            )),
+            1 => {
+                // Only enums make use of the tag; anything else sees the value.
+                self.reader.read_compressed()?;
+                de::Deserializer::deserialize_any(self, visitor)
+            }
+            3 => {
+                // The COMPLEX HEADER describes each part like a number HEADER.
+                let complex_header = self.reader.read_uint8()?;
+                let element_size = match (
+                    header::number_type(complex_header),
+                    header::byte_count(complex_header),
+                ) {
+                    (FLOAT, 1 | 2) => 2,
+                    (FLOAT | SIGNED | UNSIGNED, n @ (4 | 8 | 16))
+                    | (SIGNED | UNSIGNED, n @ (1 | 2)) => n,
+                    _ => return Err(Error::unsupported_type(start + 1, complex_header)),
+                };
+                match complex_header & 0b00000111 {
+                    0 => visitor.visit_seq(TypedSeqAccess {
+                        de: self,
+                        header: complex_header,
+                        remaining: 2,
+                        index: 0,
+                    }),
+                    1 => {
+                        let remaining = self.reader.read_count(2 * element_size)?;
+                        visitor.visit_seq(ComplexSeqAccess {
+                            de: self,
+                            header: complex_header,
+                            remaining,
+                            index: 0,
+                        })
+                    }
+                    _ => Err(Error::invalid_header(start + 1, complex_header)),
+                }
+            }
+            extension => Err(Error::unsupported_extension(start, extension)),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let start = self.reader.cursor;
+        let header = self.reader.read_uint8()?;
+
+        match header::value_type(header) {
+            header::NULL => {
+                if header & 0b00001000 != 0 {
+                    visitor.visit_bool((header & 0b11110000) >> 4 > 0)
+                } else {
+                    visitor.visit_unit()
+                }
+            }
+            header::NUMBER => self.visit_number(header, visitor),
+            header::STRING => visitor.visit_borrowed_str(self.reader.read_str()?),
+            header::OBJECT => {
+                let key_size = match (header::number_type(header), header::byte_count(header)) {
+                    (0, _) => 1,
+                    (SIGNED | UNSIGNED, n @ (1 | 2 | 4 | 8 | 16)) => n,
+                    (BOOL_OR_STRING, _) => return Err(Error::invalid_header(start, header)),
+                    _ => return Err(Error::unsupported_type(start, header)),
+                };
+                let remaining = self.reader.read_count(key_size + 1)?;
+                self.reader.enter()?;
+                let value = visitor.visit_map(MapAccess {
+                    de: self,
+                    header,
+                    remaining,
+                    key_offset: 0,
+                })?;
+                self.reader.leave();
+                Ok(value)
+            }
+            header::TYPED_ARRAY => {
+                if header == header::typed_array(BOOL_OR_STRING, 0) {
+                    let n = self.reader.read_size()?;
+                    let bits = self.reader.read_bits(n)?;
+                    return visitor.visit_seq(de::value::SeqDeserializer::new(bits.into_iter()));
+                }
+                let num_type = header::number_type(header);
+                let element_size = match (num_type, header::byte_count(header)) {
+                    (BOOL_OR_STRING, 2) => 1,
+                    (FLOAT, 1 | 2) => 2,
+                    (FLOAT | SIGNED | UNSIGNED, n @ (4 | 8 | 16))
+                    | (SIGNED | UNSIGNED, n @ (1 | 2)) => n,
+                    _ => return Err(Error::unsupported_type(start, header)),
+                };
+                let remaining = self.reader.read_count(element_size)?;
+                let mut access = TypedSeqAccess {
+                    de: self,
+                    header,
+                    remaining,
+                    index: 0,
+                };
+                let value = visitor.visit_seq(&mut access)?;
+                access.skip_rest()?;
+                Ok(value)
+            }
+            header::GENERIC_ARRAY => {
+                let remaining = self.reader.read_count(1)?;
+                self.reader.enter()?;
+                let mut access = SeqAccess {
+                    de: self,
+                    remaining,
+                    index: 0,
+                };
+                let value = visitor.visit_seq(&mut access)?;
+                access.skip_rest()?;
+                self.reader.leave();
+                Ok(value)
+            }
+            header::EXTENSION => {
+                self.reader.enter()?;
+                let value = self.visit_extension(header, visitor)?;
+                self.reader.leave();
+                Ok(value)
+            }
+            _ => Err(Error::invalid_header(start, header)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.reader.peek_uint8()? == header::NULL {
+            self.reader.read_uint8()?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.reader.peek_uint8()? == header::typed_array(UNSIGNED, 0) {
+            self.reader.read_uint8()?;
+            let n = self.reader.read_size()?;
+            visitor.visit_borrowed_bytes(self.reader.read_bytes(n)?)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let start = self.reader.cursor;
+        let header = self.reader.peek_uint8()?;
+
+        match header::value_type(header) {
+            header::STRING => {
+                self.reader.read_uint8()?;
+                visitor.visit_enum(BorrowedStrDeserializer::<Error>::new(
+                    self.reader.read_str()?,
+                ))
+            }
+            header::EXTENSION if header::extension(header) == 1 => {
+                self.reader.read_uint8()?;
+                let index = u32::try_from(self.reader.read_compressed()?)
+                    .map_err(|_| Error::size_overflow(start + 1))?;
+                self.reader.enter()?;
+                let value = visitor.visit_enum(EnumAccess {
+                    de: self,
+                    index: Some(index),
+                })?;
+                self.reader.leave();
+                Ok(value)
+            }
+            header::OBJECT if header::number_type(header) == 0 => {
+                self.reader.read_uint8()?;
+                if self.reader.read_compressed()? != 1 {
+                    return Err(Error::type_mismatch(
+                        start,
+                        "an object with a single key for an enum",
+                    ));
+                }
+                self.reader.enter()?;
+                let value = visitor.visit_enum(EnumAccess {
+                    de: self,
+                    index: None,
+                })?;
+                self.reader.leave();
+                Ok(value)
+            }
+            _ => Err(Error::type_mismatch(start, "an enum")),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: usize,
+    index: usize,
+}
+
+impl SeqAccess<'_, '_> {
+    /// Skips the elements the visitor did not ask for, such as values
+    /// appended to a struct written as an array.
+    fn skip_rest(&mut self) -> Result<(), Error> {
+        for index in self.index..self.index + self.remaining {
+            self.de.reader.skip_value().map_err(|e| e.at_index(index))?;
+        }
+        self.remaining = 0;
+        Ok(())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let index = self.index;
+        self.index += 1;
+        let start = self.de.reader.cursor;
+        seed.deserialize(&mut *self.de)
+            .map(Some)
+            .map_err(|e| e.or_offset(start).at_index(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct TypedSeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    header: u8,
+    remaining: usize,
+    index: usize,
+}
+
+impl<'de> TypedSeqAccess<'_, 'de> {
+    /// Moves past the elements the visitor did not ask for.
+    fn skip_rest(&mut self) -> Result<(), Error> {
+        while de::SeqAccess::<'de>::next_element::<IgnoredAny>(self)?.is_some() {}
+        Ok(())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for TypedSeqAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let index = self.index;
+        self.index += 1;
+        let start = self.de.reader.cursor;
+        seed.deserialize(TypedElement {
+            de: &mut *self.de,
+            header: self.header,
+        })
+        .map(Some)
+        .map_err(|e| e.or_offset(start).at_index(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// The numbers of a complex array, each a sequence of two parts.
+struct ComplexSeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    header: u8,
+    remaining: usize,
+    index: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for ComplexSeqAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let index = self.index;
+        self.index += 1;
+        let start = self.de.reader.cursor;
+        seed.deserialize(ComplexElement {
+            de: &mut *self.de,
+            header: self.header,
+        })
+        .map(Some)
+        .map_err(|e| e.or_offset(start).at_index(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// One number of a complex array: a real and an imaginary part.
+struct ComplexElement<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    header: u8,
+}
+
+impl<'de> de::Deserializer<'de> for ComplexElement<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(TypedSeqAccess {
+            de: self.de,
+            header: self.header,
+            remaining: 2,
+            index: 0,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// One element of a typed array: DATA described by the array's HEADER.
+struct TypedElement<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    header: u8,
+}
+
+impl<'de> de::Deserializer<'de> for TypedElement<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if header::number_type(self.header) == BOOL_OR_STRING {
+            visitor.visit_borrowed_str(self.de.reader.read_str()?)
+        } else {
+            self.de.visit_number(self.header, visitor)
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if header::number_type(self.header) != BOOL_OR_STRING {
+            return Err(Error::type_mismatch(self.de.reader.cursor, "an enum"));
+        }
+        visitor.visit_enum(BorrowedStrDeserializer::<Error>::new(
+            self.de.reader.read_str()?,
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Object members. Integer keys are read like typed array elements, since
+/// the object HEADER describes them the same way.
+struct MapAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    header: u8,
+    remaining: usize,
+    key_offset: usize,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        self.key_offset = self.de.reader.cursor;
+        let key = if header::number_type(self.header) == 0 {
+            let key = self.de.reader.read_str()?;
+            seed.deserialize(BorrowedStrDeserializer::<Error>::new(key))
+        } else {
+            seed.deserialize(TypedElement {
+                de: &mut *self.de,
+                header: self.header,
+            })
+        };
+        key.map(Some).map_err(|e| e.or_offset(self.key_offset))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let start = self.de.reader.cursor;
+        seed.deserialize(&mut *self.de).map_err(|e| {
+            e.or_offset(start)
+                .at_key(self.de.key_at(self.key_offset, self.header))
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// An enum either written as a type tag (`index` is set) or as an object with
+/// a single key naming the variant.
+struct EnumAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    index: Option<u32>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for EnumAccess<'a, 'de> {
+    type Error = Error;
+    type Variant = &'a mut Deserializer<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        let variant = match self.index {
+            Some(index) => seed.deserialize(IntoDeserializer::<Error>::into_deserializer(index))?,
+            None => seed.deserialize(BorrowedStrDeserializer::<Error>::new(
+                self.de.reader.read_str()?,
+            ))?,
+        };
+        Ok((variant, self.de))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
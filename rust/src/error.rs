@@ -0,0 +1,337 @@
+use std::fmt::{self, Display};
+use std::io;
+
+/// One step of a [`Path`]: an object key or an array index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Where in the document an error occurred, displayed like `$.results[4].name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Path {
+    // Innermost segment first, since segments are added while unwinding.
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    /// The segments from the root down.
+    pub fn segments(&self) -> impl Iterator<Item = &Segment> {
+        self.segments.iter().rev()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in self.segments() {
+            match segment {
+                Segment::Key(key) => write!(f, ".{key}")?,
+                Segment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors produced while reading or writing BEVE.
+///
+/// `offset` is the byte position in the input (when reading) or output (when
+/// writing) at which the problem was found.
+#[derive(Debug)]
+pub enum Error {
+    /// The input ended in the middle of a value.
+    UnexpectedEof {
+        offset: usize,
+        path: Path,
+    },
+    /// A HEADER that the specification does not define.
+    InvalidHeader {
+        offset: usize,
+        header: u8,
+        path: Path,
+    },
+    /// A valid HEADER for a type this implementation does not support.
+    UnsupportedType {
+        offset: usize,
+        header: u8,
+        path: Path,
+    },
+    /// A string that is not valid UTF-8.
+    InvalidUtf8 {
+        offset: usize,
+        path: Path,
+    },
+    /// An extension this implementation does not support.
+    UnsupportedExtension {
+        offset: usize,
+        extension: u8,
+        path: Path,
+    },
+    /// A SIZE that does not fit in memory, or in a compressed unsigned integer.
+    SizeOverflow {
+        offset: usize,
+        path: Path,
+    },
+    /// A value of a different type than the one expected.
+    TypeMismatch {
+        offset: usize,
+        expected: &'static str,
+        path: Path,
+    },
+    /// Values are nested deeper than the configured limit.
+    DepthLimit {
+        offset: usize,
+        path: Path,
+    },
+    /// Bytes left over after the value.
+    TrailingBytes {
+        offset: usize,
+    },
+    /// The output did not fit in the slice given to
+    /// [`Writer::from_slice`](crate::Writer::from_slice), which would have
+    /// to hold `needed` bytes. Sequences of booleans written through serde
+    /// take a byte per element until they end, so this can be more than the
+    /// size of the output.
+    BufferTooSmall {
+        needed: usize,
+    },
+    /// Any other error, such as one raised by a `Deserialize` implementation.
+    Message {
+        msg: String,
+        offset: Option<usize>,
+        path: Path,
+    },
+    Io(io::Error),
+}
+
+impl Error {
+    pub(crate) fn eof(offset: usize) -> Self {
+        Error::UnexpectedEof {
+            offset,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn invalid_header(offset: usize, header: u8) -> Self {
+        Error::InvalidHeader {
+            offset,
+            header,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn unsupported_type(offset: usize, header: u8) -> Self {
+        Error::UnsupportedType {
+            offset,
+            header,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn invalid_utf8(offset: usize) -> Self {
+        Error::InvalidUtf8 {
+            offset,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn unsupported_extension(offset: usize, extension: u8) -> Self {
+        Error::UnsupportedExtension {
+            offset,
+            extension,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn size_overflow(offset: usize) -> Self {
+        Error::SizeOverflow {
+            offset,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn type_mismatch(offset: usize, expected: &'static str) -> Self {
+        Error::TypeMismatch {
+            offset,
+            expected,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn depth_limit(offset: usize) -> Self {
+        Error::DepthLimit {
+            offset,
+            path: Path::default(),
+        }
+    }
+
+    pub(crate) fn message(msg: impl Into<String>) -> Self {
+        Error::Message {
+            msg: msg.into(),
+            offset: None,
+            path: Path::default(),
+        }
+    }
+
+    /// Byte offset of the error, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            Error::UnexpectedEof { offset, .. }
+            | Error::InvalidHeader { offset, .. }
+            | Error::UnsupportedType { offset, .. }
+            | Error::InvalidUtf8 { offset, .. }
+            | Error::UnsupportedExtension { offset, .. }
+            | Error::SizeOverflow { offset, .. }
+            | Error::TypeMismatch { offset, .. }
+            | Error::DepthLimit { offset, .. }
+            | Error::TrailingBytes { offset } => Some(offset),
+            Error::Message { offset, .. } => offset,
+            Error::BufferTooSmall { .. } | Error::Io(_) => None,
+        }
+    }
+
+    /// Path to the value that caused the error, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::UnexpectedEof { path, .. }
+            | Error::InvalidHeader { path, .. }
+            | Error::UnsupportedType { path, .. }
+            | Error::InvalidUtf8 { path, .. }
+            | Error::UnsupportedExtension { path, .. }
+            | Error::SizeOverflow { path, .. }
+            | Error::TypeMismatch { path, .. }
+            | Error::DepthLimit { path, .. }
+            | Error::Message { path, .. } => Some(path),
+            Error::TrailingBytes { .. } | Error::BufferTooSmall { .. } | Error::Io(_) => None,
+        }
+    }
+
+    fn path_mut(&mut self) -> Option<&mut Path> {
+        match self {
+            Error::UnexpectedEof { path, .. }
+            | Error::InvalidHeader { path, .. }
+            | Error::UnsupportedType { path, .. }
+            | Error::InvalidUtf8 { path, .. }
+            | Error::UnsupportedExtension { path, .. }
+            | Error::SizeOverflow { path, .. }
+            | Error::TypeMismatch { path, .. }
+            | Error::DepthLimit { path, .. }
+            | Error::Message { path, .. } => Some(path),
+            Error::TrailingBytes { .. } | Error::BufferTooSmall { .. } | Error::Io(_) => None,
+        }
+    }
+
+    /// Records that the error happened inside the member `key`.
+    pub(crate) fn at_key(mut self, key: impl Into<String>) -> Self {
+        if let Some(path) = self.path_mut() {
+            path.segments.push(Segment::Key(key.into()));
+        }
+        self
+    }
+
+    /// Records that the error happened inside the element `index`.
+    pub(crate) fn at_index(mut self, index: usize) -> Self {
+        if let Some(path) = self.path_mut() {
+            path.segments.push(Segment::Index(index));
+        }
+        self
+    }
+
+    /// Moves the offset of the error `delta` bytes further, for errors raised
+    /// while reading a slice of a larger input.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn offset_by(mut self, delta: usize) -> Self {
+        match &mut self {
+            Error::UnexpectedEof { offset, .. }
+            | Error::InvalidHeader { offset, .. }
+            | Error::UnsupportedType { offset, .. }
+            | Error::InvalidUtf8 { offset, .. }
+            | Error::UnsupportedExtension { offset, .. }
+            | Error::SizeOverflow { offset, .. }
+            | Error::TypeMismatch { offset, .. }
+            | Error::DepthLimit { offset, .. }
+            | Error::TrailingBytes { offset } => *offset += delta,
+            Error::Message {
+                offset: Some(offset),
+                ..
+            } => *offset += delta,
+            Error::Message { offset: None, .. } | Error::BufferTooSmall { .. } | Error::Io(_) => {}
+        }
+        self
+    }
+
+    /// Sets the offset of an error raised without one.
+    pub(crate) fn or_offset(mut self, at: usize) -> Self {
+        if let Error::Message { offset, .. } = &mut self {
+            offset.get_or_insert(at);
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnexpectedEof { .. } => f.write_str("unexpected end of input")?,
+            Error::InvalidHeader { header, .. } => write!(f, "invalid header {header:#010b}")?,
+            Error::UnsupportedType { header, .. } => {
+                write!(f, "unsupported type for header {header:#010b}")?
+            }
+            Error::InvalidUtf8 { .. } => f.write_str("invalid UTF-8 in string")?,
+            Error::UnsupportedExtension { extension, .. } => {
+                write!(f, "unsupported extension {extension}")?
+            }
+            Error::SizeOverflow { .. } => f.write_str("SIZE out of range")?,
+            Error::TypeMismatch { expected, .. } => write!(f, "expected {expected}")?,
+            Error::DepthLimit { .. } => f.write_str("nesting depth limit exceeded")?,
+            Error::TrailingBytes { .. } => f.write_str("trailing bytes after value")?,
+            Error::BufferTooSmall { needed } => {
+                write!(f, "output buffer too small, {needed} bytes needed")?
+            }
+            Error::Message { msg, .. } => f.write_str(msg)?,
+            Error::Io(err) => return Display::fmt(err, f),
+        }
+        if let Some(offset) = self.offset() {
+            write!(f, " at byte {offset}")?;
+        }
+        match self.path() {
+            Some(path) if !path.is_root() => write!(f, " ({path})"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::message(msg.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::message(msg.to_string())
+    }
+}
@@ -0,0 +1,510 @@
+// Pull parsing from an `io::Read` through a buffer of fixed size, so that
+// documents larger than memory can be processed. Strings and typed arrays
+// are handed out in chunks as they arrive.
+
+use std::io;
+
+use crate::borrowed::{Bits, KeyRef, TypedArrayRef, ValueRef};
+use crate::error::Error;
+use crate::ext::Layout;
+use crate::header::{self, BOOL_OR_STRING, FLOAT, SIGNED, UNSIGNED};
+use crate::reader::{Reader, DEFAULT_MAX_DEPTH};
+
+/// Size of the buffer of an [`EventReader`] made with [`EventReader::new`].
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Room for any HEADER, SIZE or number.
+const MIN_BUFFER_SIZE: usize = 64;
+
+/// One step through a document read by an [`EventReader`].
+///
+/// Strings, objects, arrays, typed arrays, matrices and complex numbers start
+/// with an event giving their SIZE, if they have one, and close with
+/// [`Event::End`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// Null, a boolean or a number.
+    Scalar(ValueRef<'a>),
+    /// A string of `len` bytes, whose content follows in [`Event::Str`]
+    /// chunks.
+    String {
+        len: usize,
+    },
+    /// Part of a string, cut at a character boundary.
+    Str(&'a str),
+    /// An object of `len` members, each an [`Event::Key`] followed by the
+    /// events of its value.
+    Object {
+        len: usize,
+    },
+    Key(KeyRef<'a>),
+    /// A generic array of `len` elements.
+    Array {
+        len: usize,
+    },
+    /// A typed array of `len` elements with the given HEADER. Numbers and
+    /// booleans follow in [`Event::Elements`] chunks, and strings as
+    /// [`Event::String`] events.
+    TypedArray {
+        header: u8,
+        len: usize,
+    },
+    /// Consecutive elements of a numeric or boolean typed array, or parts of
+    /// complex numbers.
+    Elements(TypedArrayRef<'a>),
+    /// A type tag, followed by the events of the tagged value. There is no
+    /// [`Event::End`] for it.
+    Variant {
+        index: u64,
+    },
+    /// A matrix, followed by its extents and its data as typed arrays. Unlike
+    /// [`Reader`], the extents are not checked against the data.
+    Matrix {
+        layout: Layout,
+    },
+    /// A complex number, whose real and imaginary parts follow as
+    /// [`Event::Elements`].
+    Complex,
+    /// An array of `len` complex numbers, whose real and imaginary parts
+    /// follow interleaved as [`Event::Elements`].
+    ComplexArray {
+        len: usize,
+    },
+    /// The end of the innermost value that is open.
+    End,
+    /// A data delimiter between top-level values.
+    Delimiter,
+}
+
+/// A value whose events are not all read yet.
+#[derive(Clone, Copy)]
+enum Frame {
+    Object {
+        header: u8,
+        remaining: usize,
+        value_next: bool,
+    },
+    Array {
+        remaining: usize,
+    },
+    Variant {
+        done: bool,
+    },
+    Matrix {
+        remaining: usize,
+    },
+    /// Bytes of a string.
+    String {
+        remaining: usize,
+    },
+    /// Strings of a string typed array.
+    Strings {
+        remaining: usize,
+    },
+    /// Numbers of a typed array, or parts of complex numbers.
+    Numbers {
+        header: u8,
+        remaining: usize,
+    },
+    Bits {
+        remaining: usize,
+    },
+}
+
+/// Reads BEVE values from an [`io::Read`] as a sequence of [`Event`]s,
+/// holding no more of the input than fits in a buffer of fixed size.
+///
+/// Headers and SIZEs are decoded as they arrive, and strings and typed arrays
+/// are streamed through in chunks, so documents need not fit in memory.
+/// Object keys must fit in the buffer.
+pub struct EventReader<R> {
+    reader: R,
+    buffer: Box<[u8]>,
+    /// The bytes read from `reader` and not consumed yet are
+    /// `buffer[start..end]`.
+    start: usize,
+    end: usize,
+    /// Offset in the input of `buffer[0]`.
+    offset: usize,
+    eof: bool,
+    /// Innermost last.
+    stack: Vec<Frame>,
+    max_depth: usize,
+}
+
+impl<R: io::Read> EventReader<R> {
+    pub fn new(reader: R) -> Self {
+        EventReader::with_capacity(DEFAULT_BUFFER_SIZE, reader)
+    }
+
+    /// Reads through a buffer of `capacity` bytes, or of 64 bytes if that is
+    /// more.
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        EventReader {
+            reader,
+            buffer: vec![0; capacity.max(MIN_BUFFER_SIZE)].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            offset: 0,
+            eof: false,
+            stack: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Limits how deeply values may nest before reading fails with
+    /// [`Error::DepthLimit`].
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Byte offset in the input of the next event.
+    pub fn position(&self) -> usize {
+        self.offset + self.start
+    }
+
+    /// Reads the next event, or returns `None` when the input ends between
+    /// top-level values.
+    pub fn next_event(&mut self) -> Result<Option<Event<'_>>, Error> {
+        loop {
+            let Some(frame) = self.stack.pop() else {
+                if self.fill(1)? == 0 {
+                    return Ok(None);
+                }
+                if self.buffer[self.start] == header::DELIMITER {
+                    self.start += 1;
+                    return Ok(Some(Event::Delimiter));
+                }
+                return self.begin_value().map(Some);
+            };
+
+            let event = match frame {
+                Frame::Object {
+                    header,
+                    remaining,
+                    value_next: true,
+                } => {
+                    self.stack.push(Frame::Object {
+                        header,
+                        remaining,
+                        value_next: false,
+                    });
+                    self.begin_value()?
+                }
+                Frame::Object { remaining: 0, .. }
+                | Frame::Array { remaining: 0 }
+                | Frame::Matrix { remaining: 0 }
+                | Frame::String { remaining: 0 }
+                | Frame::Strings { remaining: 0 }
+                | Frame::Numbers { remaining: 0, .. }
+                | Frame::Bits { remaining: 0 } => Event::End,
+                Frame::Object {
+                    header, remaining, ..
+                } => {
+                    self.stack.push(Frame::Object {
+                        header,
+                        remaining: remaining - 1,
+                        value_next: true,
+                    });
+                    Event::Key(self.read_key(header)?)
+                }
+                Frame::Array { remaining } => {
+                    self.stack.push(Frame::Array {
+                        remaining: remaining - 1,
+                    });
+                    self.begin_value()?
+                }
+                Frame::Variant { done: false } => {
+                    self.stack.push(Frame::Variant { done: true });
+                    self.begin_value()?
+                }
+                Frame::Variant { done: true } => continue,
+                Frame::Matrix { remaining } => {
+                    self.stack.push(Frame::Matrix {
+                        remaining: remaining - 1,
+                    });
+                    self.require(1)?;
+                    if header::value_type(self.buffer[self.start]) != header::TYPED_ARRAY {
+                        return Err(Error::type_mismatch(self.position(), "a typed array"));
+                    }
+                    self.begin_value()?
+                }
+                Frame::String { remaining } => self.read_str_chunk(remaining)?,
+                Frame::Strings { remaining } => {
+                    self.stack.push(Frame::Strings {
+                        remaining: remaining - 1,
+                    });
+                    let len = self.parse(|reader| reader.read_size())?;
+                    self.stack.push(Frame::String { remaining: len });
+                    Event::String { len }
+                }
+                Frame::Numbers { header, remaining } => {
+                    let size = element_size(header);
+                    let available = self.require_chunk(size, remaining.saturating_mul(size))?;
+                    let n = (available / size).min(remaining);
+                    let mut reader = Reader::new(&self.buffer[self.start..self.end]);
+                    let elements = reader.read_numbers_ref(header, self.position(), n)?;
+                    self.start += n * size;
+                    self.stack.push(Frame::Numbers {
+                        header,
+                        remaining: remaining - n,
+                    });
+                    Event::Elements(elements)
+                }
+                Frame::Bits { remaining } => {
+                    let available = self.require_chunk(1, remaining.div_ceil(8))?;
+                    let n = available.min(remaining.div_ceil(8));
+                    let len = (n * 8).min(remaining);
+                    let bits = Bits::new(&self.buffer[self.start..self.start + n], len);
+                    self.start += n;
+                    self.stack.push(Frame::Bits {
+                        remaining: remaining - len,
+                    });
+                    Event::Elements(TypedArrayRef::Bool(bits))
+                }
+            };
+            return Ok(Some(event));
+        }
+    }
+
+    /// Reads a HEADER and whatever comes before the content of the value.
+    fn begin_value(&mut self) -> Result<Event<'_>, Error> {
+        let at = self.position();
+        self.require(1)?;
+        let header = self.buffer[self.start];
+
+        match header::value_type(header) {
+            header::NULL | header::NUMBER => {
+                self.fill(17)?;
+                let mut reader = Reader::new(&self.buffer[self.start..self.end]);
+                let value = reader.read_value_ref().map_err(|e| e.offset_by(at))?;
+                self.start += reader.position();
+                Ok(Event::Scalar(value))
+            }
+            header::STRING => {
+                self.start += 1;
+                let len = self.parse(|reader| reader.read_size())?;
+                self.stack.push(Frame::String { remaining: len });
+                Ok(Event::String { len })
+            }
+            header::OBJECT => {
+                match (header::number_type(header), header::byte_count(header)) {
+                    (0, _) | (SIGNED | UNSIGNED, 1 | 2 | 4 | 8 | 16) => {}
+                    (BOOL_OR_STRING, _) => return Err(Error::invalid_header(at, header)),
+                    _ => return Err(Error::unsupported_type(at, header)),
+                }
+                self.start += 1;
+                let len = self.parse(|reader| reader.read_size())?;
+                self.enter(
+                    Frame::Object {
+                        header,
+                        remaining: len,
+                        value_next: false,
+                    },
+                    at,
+                )?;
+                Ok(Event::Object { len })
+            }
+            header::TYPED_ARRAY => {
+                self.start += 1;
+                let len = self.parse(|reader| reader.read_size())?;
+                self.stack
+                    .push(if header::number_type(header) != BOOL_OR_STRING {
+                        // Only checks the HEADER.
+                        Reader::new(&[]).read_numbers_ref(header, at, 0)?;
+                        Frame::Numbers {
+                            header,
+                            remaining: len,
+                        }
+                    } else if header & 0b00100000 != 0 {
+                        Frame::Strings { remaining: len }
+                    } else {
+                        Frame::Bits { remaining: len }
+                    });
+                Ok(Event::TypedArray { header, len })
+            }
+            header::GENERIC_ARRAY => {
+                self.start += 1;
+                let len = self.parse(|reader| reader.read_size())?;
+                self.enter(Frame::Array { remaining: len }, at)?;
+                Ok(Event::Array { len })
+            }
+            header::EXTENSION => {
+                self.start += 1;
+                match header::extension(header) {
+                    0 => Err(Error::type_mismatch(
+                        at,
+                        "a value rather than a data delimiter",
+                    )),
+                    1 => {
+                        let index = self.parse(|reader| reader.read_compressed())?;
+                        self.enter(Frame::Variant { done: false }, at)?;
+                        Ok(Event::Variant { index })
+                    }
+                    2 => {
+                        let layout = match self.parse(|reader| reader.read_uint8())? & 0b00000001 {
+                            0 => Layout::Right,
+                            _ => Layout::Left,
+                        };
+                        self.enter(Frame::Matrix { remaining: 2 }, at)?;
+                        Ok(Event::Matrix { layout })
+                    }
+                    3 => {
+                        let complex_header = self.parse(|reader| reader.read_uint8())?;
+                        if header::number_type(complex_header) == BOOL_OR_STRING {
+                            return Err(Error::invalid_header(at + 1, complex_header));
+                        }
+                        let array_header = (complex_header & !0b00000111) | header::TYPED_ARRAY;
+                        Reader::new(&[]).read_numbers_ref(array_header, at + 1, 0)?;
+                        let (event, parts) = match complex_header & 0b00000111 {
+                            0 => (Event::Complex, 2),
+                            1 => {
+                                let len = self.parse(|reader| reader.read_size())?;
+                                let parts = len
+                                    .checked_mul(2)
+                                    .ok_or_else(|| Error::size_overflow(at + 2))?;
+                                (Event::ComplexArray { len }, parts)
+                            }
+                            _ => return Err(Error::invalid_header(at + 1, complex_header)),
+                        };
+                        self.enter(
+                            Frame::Numbers {
+                                header: array_header,
+                                remaining: parts,
+                            },
+                            at,
+                        )?;
+                        Ok(event)
+                    }
+                    extension => Err(Error::unsupported_extension(at, extension)),
+                }
+            }
+            _ => Err(Error::invalid_header(at, header)),
+        }
+    }
+
+    /// Opens an object, a generic array or an extension, as [`Reader`] counts
+    /// them towards the depth limit.
+    fn enter(&mut self, frame: Frame, at: usize) -> Result<(), Error> {
+        if self.stack.len() >= self.max_depth {
+            return Err(Error::depth_limit(at));
+        }
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    fn read_key(&mut self, header: u8) -> Result<KeyRef<'_>, Error> {
+        let at = self.position();
+        if header::number_type(header) != 0 {
+            self.fill(16)?;
+            let mut reader = Reader::new(&self.buffer[self.start..self.end]);
+            let key = reader.read_key_ref(header).map_err(|e| e.offset_by(at))?;
+            self.start += reader.position();
+            return Ok(key);
+        }
+
+        let len = self.parse(|reader| reader.read_size())?;
+        if len > self.buffer.len() {
+            return Err(Error::type_mismatch(at, "a key that fits in the buffer"));
+        }
+        self.require(len)?;
+        let key = std::str::from_utf8(&self.buffer[self.start..self.start + len])
+            .map_err(|_| Error::invalid_utf8(self.offset + self.start))?;
+        self.start += len;
+        Ok(KeyRef::String(key))
+    }
+
+    /// Reads as much of a string as is buffered, up to `remaining` bytes.
+    fn read_str_chunk(&mut self, remaining: usize) -> Result<Event<'_>, Error> {
+        // Four bytes hold any character.
+        let available = self.require_chunk(remaining.min(4), remaining)?;
+        let at = self.position();
+        let bytes = &self.buffer[self.start..self.start + available.min(remaining)];
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text,
+            // A character cut off at the end of the buffer is left for the
+            // next chunk.
+            Err(err) if err.valid_up_to() > 0 => std::str::from_utf8(&bytes[..err.valid_up_to()])
+                .map_err(|_| Error::invalid_utf8(at))?,
+            Err(err) => return Err(Error::invalid_utf8(at + err.valid_up_to())),
+        };
+        self.start += text.len();
+        self.stack.push(Frame::String {
+            remaining: remaining - text.len(),
+        });
+        Ok(Event::Str(text))
+    }
+
+    /// Runs `f` on the buffered bytes, after buffering enough for a SIZE or
+    /// a number if the input has them, and consumes what it read.
+    fn parse<T>(
+        &mut self,
+        f: impl FnOnce(&mut Reader<'_>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.fill(17)?;
+        let at = self.position();
+        let mut reader = Reader::new(&self.buffer[self.start..self.end]);
+        let value = f(&mut reader).map_err(|e| e.offset_by(at))?;
+        self.start += reader.position();
+        Ok(value)
+    }
+
+    /// Buffers at least `n` bytes, which must fit in the buffer, and returns
+    /// how many are buffered. Fails if the input ends first.
+    fn require(&mut self, n: usize) -> Result<usize, Error> {
+        let available = self.fill(n)?;
+        if available < n {
+            return Err(Error::eof(self.position() + available));
+        }
+        Ok(available)
+    }
+
+    /// Buffers at least `n` bytes like [`require`](Self::require) for the next
+    /// chunk of a string or typed array of `len` more bytes. When less than
+    /// half the buffer is left, it is topped up towards `len` first, so that
+    /// chunks stay large however little each read returns.
+    fn require_chunk(&mut self, n: usize, len: usize) -> Result<usize, Error> {
+        let available = self.require(n)?;
+        if available < len && available < self.buffer.len() / 2 {
+            return self.fill(len);
+        }
+        Ok(available)
+    }
+
+    /// Buffers at least `n` bytes, or as many as the buffer holds, unless the
+    /// input ends first, and returns how many are buffered.
+    fn fill(&mut self, n: usize) -> Result<usize, Error> {
+        let n = n.min(self.buffer.len());
+        if self.end - self.start >= n || self.eof {
+            return Ok(self.end - self.start);
+        }
+
+        // Move the unconsumed bytes to the front to make room.
+        self.buffer.copy_within(self.start..self.end, 0);
+        self.offset += self.start;
+        self.end -= self.start;
+        self.start = 0;
+
+        while self.end < n {
+            match self.reader.read(&mut self.buffer[self.end..]) {
+                Ok(0) => {
+                    self.eof = true;
+                    break;
+                }
+                Ok(read) => self.end += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(self.end)
+    }
+}
+
+/// Bytes taken by each element of a numeric typed array.
+fn element_size(header: u8) -> usize {
+    match (header::number_type(header), header::byte_count(header)) {
+        // bfloat16 uses a BYTE COUNT of 1 although it takes 2 bytes.
+        (FLOAT, 1) => 2,
+        (_, n) => n,
+    }
+}
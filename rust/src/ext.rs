@@ -0,0 +1,381 @@
+// Extensions (HEADER type 6): type tags, matrices and complex numbers.
+
+use num_complex::Complex;
+
+use crate::borrowed::{TypedArrayRef, ValueRef};
+use crate::error::Error;
+use crate::header::{self, BOOL_OR_STRING, FLOAT, SIGNED, UNSIGNED};
+use crate::reader::Reader;
+use crate::value::{TypedArray, Value, BF16, F128, F16};
+use crate::writer::Writer;
+
+/// Storage order of matrix elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// Row-major.
+    Right,
+    /// Column-major.
+    Left,
+}
+
+/// A matrix: N-dimensional extents plus the elements as one flat buffer,
+/// stored in the order given by `layout`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    pub layout: Layout,
+    pub extents: Vec<u64>,
+    pub data: Vec<T>,
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Numeric types that can be the elements of a [`Matrix`] or the parts of a
+/// complex number.
+pub trait Element: bytemuck::Pod + private::Sealed {
+    #[doc(hidden)]
+    const ARRAY_HEADER: u8;
+    #[doc(hidden)]
+    fn append(self, writer: &mut Writer<'_>) -> Result<(), Error>;
+    #[doc(hidden)]
+    fn into_typed_array(values: Vec<Self>) -> TypedArray;
+    #[doc(hidden)]
+    fn from_typed_array(array: TypedArray) -> Result<Vec<Self>, TypedArray>;
+    #[doc(hidden)]
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+    #[doc(hidden)]
+    fn cast_slice(bytes: &[u8]) -> Option<&[Self]>;
+}
+
+macro_rules! impl_element {
+    ($($t:ty => $variant:ident, $num_type:expr, $byte_count_index:expr, $v:ident => $bits:expr, $from_bits:expr;)*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl Element for $t {
+                const ARRAY_HEADER: u8 = header::typed_array($num_type, $byte_count_index);
+
+                fn append(self, writer: &mut Writer<'_>) -> Result<(), Error> {
+                    let $v = self;
+                    writer.append_bytes(&$bits.to_le_bytes())
+                }
+
+                fn into_typed_array(values: Vec<Self>) -> TypedArray {
+                    TypedArray::$variant(values)
+                }
+
+                fn from_typed_array(array: TypedArray) -> Result<Vec<Self>, TypedArray> {
+                    match array {
+                        TypedArray::$variant(values) => Ok(values),
+                        array => Err(array),
+                    }
+                }
+
+                fn from_le_bytes(bytes: &[u8]) -> Self {
+                    let bits = <[u8; std::mem::size_of::<$t>()]>::try_from(bytes).unwrap();
+                    $from_bits(bits)
+                }
+
+                fn cast_slice(bytes: &[u8]) -> Option<&[Self]> {
+                    bytemuck::try_cast_slice(bytes).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_element! {
+    i8 => I8, SIGNED, 0, v => v, i8::from_le_bytes;
+    i16 => I16, SIGNED, 1, v => v, i16::from_le_bytes;
+    i32 => I32, SIGNED, 2, v => v, i32::from_le_bytes;
+    i64 => I64, SIGNED, 3, v => v, i64::from_le_bytes;
+    i128 => I128, SIGNED, 4, v => v, i128::from_le_bytes;
+    u8 => U8, UNSIGNED, 0, v => v, u8::from_le_bytes;
+    u16 => U16, UNSIGNED, 1, v => v, u16::from_le_bytes;
+    u32 => U32, UNSIGNED, 2, v => v, u32::from_le_bytes;
+    u64 => U64, UNSIGNED, 3, v => v, u64::from_le_bytes;
+    u128 => U128, UNSIGNED, 4, v => v, u128::from_le_bytes;
+    BF16 => BF16, FLOAT, 0, v => v.0, |b| BF16(u16::from_le_bytes(b));
+    F16 => F16, FLOAT, 1, v => v.0, |b| F16(u16::from_le_bytes(b));
+    f32 => F32, FLOAT, 2, v => v, f32::from_le_bytes;
+    f64 => F64, FLOAT, 3, v => v, f64::from_le_bytes;
+    F128 => F128, FLOAT, 4, v => v.0, |b| F128(u128::from_le_bytes(b));
+}
+
+impl<T: Element> From<Matrix<T>> for Value {
+    fn from(m: Matrix<T>) -> Self {
+        Value::Matrix {
+            layout: m.layout,
+            extents: m.extents,
+            data: T::into_typed_array(m.data),
+        }
+    }
+}
+
+impl<T: Element> TryFrom<Value> for Matrix<T> {
+    type Error = Value;
+
+    /// Takes a matrix with elements of type `T` out of a value, or gives the
+    /// value back unchanged.
+    fn try_from(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Matrix {
+                layout,
+                extents,
+                data,
+            } => match T::from_typed_array(data) {
+                Ok(data) => Ok(Matrix {
+                    layout,
+                    extents,
+                    data,
+                }),
+                Err(data) => Err(Value::Matrix {
+                    layout,
+                    extents,
+                    data,
+                }),
+            },
+            value => Err(value),
+        }
+    }
+}
+
+impl<T: Element> From<Complex<T>> for Value {
+    fn from(c: Complex<T>) -> Self {
+        Value::Complex(T::into_typed_array(vec![c.re, c.im]))
+    }
+}
+
+impl<T: Element> From<Vec<Complex<T>>> for Value {
+    fn from(v: Vec<Complex<T>>) -> Self {
+        let parts = bytemuck::cast_slice(&v).to_vec();
+        Value::ComplexArray(T::into_typed_array(parts))
+    }
+}
+
+impl<T: Element> TryFrom<Value> for Complex<T> {
+    type Error = Value;
+
+    fn try_from(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::Complex(parts) if parts.len() == 2 => match T::from_typed_array(parts) {
+                Ok(parts) => Ok(Complex::new(parts[0], parts[1])),
+                Err(parts) => Err(Value::Complex(parts)),
+            },
+            value => Err(value),
+        }
+    }
+}
+
+impl<T: Element> TryFrom<Value> for Vec<Complex<T>> {
+    type Error = Value;
+
+    fn try_from(value: Value) -> Result<Self, Value> {
+        match value {
+            Value::ComplexArray(parts) if parts.len() % 2 == 0 => {
+                match T::from_typed_array(parts) {
+                    Ok(parts) => Ok(parts
+                        .chunks_exact(2)
+                        .map(|c| Complex::new(c[0], c[1]))
+                        .collect()),
+                    Err(parts) => Err(Value::ComplexArray(parts)),
+                }
+            }
+            value => Err(value),
+        }
+    }
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn read_extension(&mut self, header: u8) -> Result<Value, Error> {
+        // The tagged value is read as owned, so that it honours the settings
+        // of this reader.
+        if header::extension(header) == 1 {
+            let index = self.read_compressed()?;
+            let value = self.read_value()?;
+            return Ok(Value::Variant {
+                index,
+                value: Box::new(value),
+            });
+        }
+
+        let value = match self.read_extension_ref(header)? {
+            ValueRef::Matrix {
+                layout,
+                extents,
+                data,
+            } => Value::Matrix {
+                layout,
+                extents,
+                data: self.copy_typed_array(data),
+            },
+            ValueRef::Complex(parts) => Value::Complex(self.copy_typed_array(parts)),
+            ValueRef::ComplexArray(parts) => Value::ComplexArray(self.copy_typed_array(parts)),
+            value => value.to_value(),
+        };
+        Ok(value)
+    }
+
+    pub(crate) fn read_extension_ref(&mut self, header: u8) -> Result<ValueRef<'a>, Error> {
+        let start = self.cursor - 1;
+        match header::extension(header) {
+            0 => Err(Error::type_mismatch(
+                start,
+                "a value rather than a data delimiter",
+            )),
+            1 => {
+                let index = self.read_compressed()?;
+                let value = self.read_value_ref()?;
+                Ok(ValueRef::Variant {
+                    index,
+                    value: Box::new(value),
+                })
+            }
+            2 => {
+                let layout = match self.read_uint8()? & 0b00000001 {
+                    0 => Layout::Right,
+                    _ => Layout::Left,
+                };
+
+                let extents_offset = self.cursor;
+                let extents: Vec<u64> = match self.read_value_ref()? {
+                    ValueRef::TypedArray(TypedArrayRef::U8(e)) => e.iter().map(u64::from).collect(),
+                    ValueRef::TypedArray(TypedArrayRef::U16(e)) => {
+                        e.iter().map(u64::from).collect()
+                    }
+                    ValueRef::TypedArray(TypedArrayRef::U32(e)) => {
+                        e.iter().map(u64::from).collect()
+                    }
+                    ValueRef::TypedArray(TypedArrayRef::U64(e)) => e.to_vec(),
+                    _ => {
+                        return Err(Error::type_mismatch(
+                            extents_offset,
+                            "a typed array of unsigned extents",
+                        ))
+                    }
+                };
+
+                let data_offset = self.cursor;
+                let data = match self.read_value_ref()? {
+                    ValueRef::TypedArray(data)
+                        if !matches!(data, TypedArrayRef::Bool(_) | TypedArrayRef::String(_)) =>
+                    {
+                        data
+                    }
+                    _ => {
+                        return Err(Error::type_mismatch(
+                            data_offset,
+                            "a typed array of numerical data",
+                        ))
+                    }
+                };
+                let len = extents
+                    .iter()
+                    .try_fold(1u64, |len, &extent| len.checked_mul(extent));
+                if len != Some(data.len() as u64) {
+                    return Err(Error::type_mismatch(
+                        data_offset,
+                        "as many elements as the extents describe",
+                    ));
+                }
+
+                Ok(ValueRef::Matrix {
+                    layout,
+                    extents,
+                    data,
+                })
+            }
+            3 => {
+                let complex_header = self.read_uint8()?;
+                if header::number_type(complex_header) == BOOL_OR_STRING {
+                    return Err(Error::invalid_header(start + 1, complex_header));
+                }
+                let array_header = (complex_header & !0b00000111) | header::TYPED_ARRAY;
+                match complex_header & 0b00000111 {
+                    0 => {
+                        let parts = self.read_numbers_ref(array_header, start + 1, 2)?;
+                        Ok(ValueRef::Complex(parts))
+                    }
+                    1 => {
+                        let size_offset = self.cursor;
+                        let n = self.read_size()?;
+                        let n = n
+                            .checked_mul(2)
+                            .ok_or_else(|| Error::size_overflow(size_offset))?;
+                        let parts = self.read_numbers_ref(array_header, start + 1, n)?;
+                        Ok(ValueRef::ComplexArray(parts))
+                    }
+                    _ => Err(Error::invalid_header(start + 1, complex_header)),
+                }
+            }
+            extension => Err(Error::unsupported_extension(start, extension)),
+        }
+    }
+}
+
+impl Writer<'_> {
+    /// Writes a matrix. Extents are written as a typed array of `u64`. Fails,
+    /// writing nothing, unless the data holds as many elements as the extents
+    /// describe.
+    pub fn write_matrix<T: Element>(&mut self, matrix: &Matrix<T>) -> Result<(), Error> {
+        self.write_matrix_header(matrix.layout, &matrix.extents, matrix.data.len())?;
+        self.append_uint8(T::ARRAY_HEADER)?;
+        self.encode_compressed(matrix.data.len() as u64)?;
+        self.append_numbers(&matrix.data)
+    }
+
+    /// Writes a complex number.
+    pub fn write_complex<T: Element>(&mut self, value: Complex<T>) -> Result<(), Error> {
+        self.append_uint8(header::EXTENSION | (3 << 3))?;
+        self.append_uint8(T::ARRAY_HEADER & !0b00000111)?;
+        value.re.append(self)?;
+        value.im.append(self)
+    }
+
+    /// Writes a complex array.
+    pub fn write_complex_array<T: Element>(&mut self, values: &[Complex<T>]) -> Result<(), Error> {
+        self.append_uint8(header::EXTENSION | (3 << 3))?;
+        self.append_uint8((T::ARRAY_HEADER & !0b00000111) | 1)?;
+        self.encode_compressed(values.len() as u64)?;
+        // Complex<T> is laid out as its real and imaginary parts.
+        self.append_numbers::<T>(bytemuck::cast_slice(values))
+    }
+
+    /// Appends the DATA of a numeric typed array, copying it in one go on
+    /// little-endian platforms.
+    pub(crate) fn append_numbers<T: Element>(&mut self, values: &[T]) -> Result<(), Error> {
+        if cfg!(target_endian = "little") {
+            self.append_bytes(bytemuck::cast_slice(values))
+        } else {
+            values.iter().try_for_each(|&v| v.append(self))
+        }
+    }
+
+    /// Writes everything of a matrix but its VALUE, which must hold `len`
+    /// elements. Writes nothing when that is not the product of the extents.
+    pub(crate) fn write_matrix_header(
+        &mut self,
+        layout: Layout,
+        extents: &[u64],
+        len: usize,
+    ) -> Result<(), Error> {
+        let product = extents
+            .iter()
+            .try_fold(1u64, |product, &extent| product.checked_mul(extent));
+        if product != Some(len as u64) {
+            return Err(Error::type_mismatch(
+                self.len(),
+                "as many elements as the extents describe",
+            ));
+        }
+        self.append_uint8(header::EXTENSION | (2 << 3))?;
+        self.append_uint8(match layout {
+            Layout::Right => 0,
+            Layout::Left => 1,
+        })?;
+        self.append_uint8(u64::ARRAY_HEADER)?;
+        self.encode_compressed(extents.len() as u64)?;
+        self.append_numbers(extents)
+    }
+}
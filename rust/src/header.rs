@@ -0,0 +1,51 @@
+// HEADER layout helpers shared by the reader and the writer.
+//
+// The first three bits of every HEADER denote the value type. Numbers, typed
+// arrays and integer keyed objects use the next two bits for the numeric type
+// and the top three bits for the BYTE COUNT.
+
+pub(crate) const NULL: u8 = 0;
+pub(crate) const NUMBER: u8 = 1;
+pub(crate) const STRING: u8 = 2;
+pub(crate) const OBJECT: u8 = 3;
+pub(crate) const TYPED_ARRAY: u8 = 4;
+pub(crate) const GENERIC_ARRAY: u8 = 5;
+pub(crate) const EXTENSION: u8 = 6;
+
+/// The data delimiter: extension 0, without any DATA.
+pub(crate) const DELIMITER: u8 = EXTENSION;
+
+pub(crate) const FLOAT: u8 = 0;
+pub(crate) const SIGNED: u8 = 1;
+pub(crate) const UNSIGNED: u8 = 2;
+pub(crate) const BOOL_OR_STRING: u8 = 3;
+
+pub(crate) const BYTE_COUNT: [usize; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
+
+pub(crate) fn value_type(header: u8) -> u8 {
+    header & 0b00000111
+}
+
+pub(crate) fn number_type(header: u8) -> u8 {
+    (header & 0b00011000) >> 3
+}
+
+pub(crate) fn byte_count(header: u8) -> usize {
+    BYTE_COUNT[((header & 0b11100000) >> 5) as usize]
+}
+
+pub(crate) fn extension(header: u8) -> u8 {
+    (header & 0b11111000) >> 3
+}
+
+pub(crate) const fn number(num_type: u8, byte_count_index: u8) -> u8 {
+    NUMBER | (num_type << 3) | (byte_count_index << 5)
+}
+
+pub(crate) const fn typed_array(num_type: u8, byte_count_index: u8) -> u8 {
+    TYPED_ARRAY | (num_type << 3) | (byte_count_index << 5)
+}
+
+pub(crate) const fn object(key_type: u8, byte_count_index: u8) -> u8 {
+    OBJECT | (key_type << 3) | (byte_count_index << 5)
+}
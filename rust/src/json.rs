@@ -0,0 +1,478 @@
+//! Conversion between BEVE and JSON.
+//!
+//! BEVE to JSON follows the forms given in the specification:
+//!
+//! - integer keys become string keys,
+//! - typed arrays become arrays,
+//! - a variant becomes `{"index": ..., "value": ...}`,
+//! - a matrix becomes `{"layout": "layout_right", "extents": [...], "value": [...]}`,
+//! - a complex number becomes `[re, im]` and a complex array `[[re, im], ...]`,
+//! - a data delimiter becomes a newline, so a stream of records becomes
+//!   newline delimited JSON.
+//!
+//! Non-finite floats become `null`. float128 numbers are narrowed to `f64`.
+//!
+//! JSON to BEVE writes integers as `i64` (`u64` when too large) and other
+//! numbers as `f64`. Arrays whose elements are all booleans, all strings or
+//! all numbers become typed arrays; other arrays, and arrays of integers
+//! that fit neither `i64` nor `u64`, become generic arrays.
+//! Objects in the variant and matrix forms become variants and matrices.
+//! Complex numbers cannot be told apart from arrays and stay arrays. Each
+//! top-level value after the first is preceded by a data delimiter.
+
+use std::fmt::Write as _;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::borrowed::{KeyRef, Numbers, TypedArrayRef, ValueRef};
+use crate::error::Error;
+use crate::ext::{Element, Layout};
+use crate::reader::Reader;
+use crate::value::{Key, TypedArray, Value};
+use crate::writer::Writer;
+
+/// Converts every value in `bytes` to JSON.
+pub fn to_json(bytes: &[u8]) -> Result<String, Error> {
+    let mut out = String::new();
+    let mut reader = Reader::new(bytes);
+    while reader.remaining() > 0 {
+        if reader.read_delimiter() {
+            out.push('\n');
+            continue;
+        }
+        // Values that follow each other directly still need a separator.
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        write_value(&mut out, &reader.read_value_ref()?);
+    }
+    Ok(out)
+}
+
+/// Like [`to_json`], with each value indented over several lines.
+pub fn to_json_pretty(bytes: &[u8]) -> Result<String, Error> {
+    let mut out = String::new();
+    let mut compact = String::new();
+    let mut reader = Reader::new(bytes);
+    while reader.remaining() > 0 {
+        if reader.read_delimiter() {
+            out.push('\n');
+            continue;
+        }
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        compact.clear();
+        write_value(&mut compact, &reader.read_value_ref()?);
+        indent(&mut out, &compact);
+    }
+    Ok(out)
+}
+
+/// Converts JSON, or newline delimited JSON, to BEVE.
+pub fn from_json(json: &str) -> Result<Vec<u8>, Error> {
+    let mut writer = Writer::default();
+    let values = serde_json::Deserializer::from_str(json).into_iter::<Json>();
+    for (i, value) in values.enumerate() {
+        let Json(value) = value.map_err(|e| Error::message(e.to_string()))?;
+        if i > 0 {
+            writer.write_delimiter()?;
+        }
+        writer.write_value(&value)?;
+    }
+    Ok(writer.into_bytes())
+}
+
+fn write_value(out: &mut String, value: &ValueRef<'_>) {
+    match value {
+        ValueRef::Null => out.push_str("null"),
+        ValueRef::Bool(v) => write_display(out, v),
+        ValueRef::I8(v) => write_display(out, v),
+        ValueRef::I16(v) => write_display(out, v),
+        ValueRef::I32(v) => write_display(out, v),
+        ValueRef::I64(v) => write_display(out, v),
+        ValueRef::I128(v) => write_display(out, v),
+        ValueRef::U8(v) => write_display(out, v),
+        ValueRef::U16(v) => write_display(out, v),
+        ValueRef::U32(v) => write_display(out, v),
+        ValueRef::U64(v) => write_display(out, v),
+        ValueRef::U128(v) => write_display(out, v),
+        ValueRef::BF16(v) => write_f32(out, v.to_f32()),
+        ValueRef::F16(v) => write_f32(out, v.to_f32()),
+        ValueRef::F32(v) => write_f32(out, *v),
+        ValueRef::F64(v) => write_f64(out, *v),
+        ValueRef::F128(v) => write_f64(out, v.to_f64()),
+        ValueRef::String(s) => write_str(out, s),
+        ValueRef::Object(members) => {
+            out.push('{');
+            for (i, (key, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                match key {
+                    KeyRef::String(s) => write_str(out, s),
+                    key => write_str(out, &key.to_string()),
+                }
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+        ValueRef::TypedArray(array) => write_typed_array(out, array, false),
+        ValueRef::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, value);
+            }
+            out.push(']');
+        }
+        ValueRef::Variant { index, value } => {
+            let _ = write!(out, "{{\"index\":{index},\"value\":");
+            write_value(out, value);
+            out.push('}');
+        }
+        ValueRef::Matrix {
+            layout,
+            extents,
+            data,
+        } => {
+            out.push_str(match layout {
+                Layout::Right => "{\"layout\":\"layout_right\",\"extents\":[",
+                Layout::Left => "{\"layout\":\"layout_left\",\"extents\":[",
+            });
+            for (i, extent) in extents.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_display(out, extent);
+            }
+            out.push_str("],\"value\":");
+            write_typed_array(out, data, false);
+            out.push('}');
+        }
+        ValueRef::Complex(parts) => write_typed_array(out, parts, false),
+        ValueRef::ComplexArray(parts) => write_typed_array(out, parts, true),
+    }
+}
+
+/// Writes the elements of a typed array, grouped in `[re, im]` pairs when
+/// `pairs` is set.
+fn write_typed_array(out: &mut String, array: &TypedArrayRef<'_>, pairs: bool) {
+    fn write_all<T>(
+        out: &mut String,
+        values: impl Iterator<Item = T>,
+        pairs: bool,
+        write: impl Fn(&mut String, T),
+    ) {
+        out.push('[');
+        for (i, value) in values.enumerate() {
+            match (pairs, i % 2) {
+                (true, 0) if i > 0 => out.push_str(",["),
+                (true, 0) => out.push('['),
+                _ if i > 0 => out.push(','),
+                _ => {}
+            }
+            write(out, value);
+            if pairs && i % 2 == 1 {
+                out.push(']');
+            }
+        }
+        out.push(']');
+    }
+
+    fn numbers<T: Element + std::fmt::Display>(out: &mut String, v: &Numbers<'_, T>, pairs: bool) {
+        write_all(out, v.iter(), pairs, |out, v| write_display(out, &v));
+    }
+
+    match array {
+        TypedArrayRef::Bool(v) => write_all(out, v.iter(), pairs, |out, v| write_display(out, &v)),
+        TypedArrayRef::String(v) => write_all(out, v.iter(), pairs, write_str),
+        TypedArrayRef::I8(v) => numbers(out, v, pairs),
+        TypedArrayRef::I16(v) => numbers(out, v, pairs),
+        TypedArrayRef::I32(v) => numbers(out, v, pairs),
+        TypedArrayRef::I64(v) => numbers(out, v, pairs),
+        TypedArrayRef::I128(v) => numbers(out, v, pairs),
+        TypedArrayRef::U8(v) => numbers(out, v, pairs),
+        TypedArrayRef::U16(v) => numbers(out, v, pairs),
+        TypedArrayRef::U32(v) => numbers(out, v, pairs),
+        TypedArrayRef::U64(v) => numbers(out, v, pairs),
+        TypedArrayRef::U128(v) => numbers(out, v, pairs),
+        TypedArrayRef::BF16(v) => {
+            write_all(out, v.iter(), pairs, |out, v| write_f32(out, v.to_f32()))
+        }
+        TypedArrayRef::F16(v) => {
+            write_all(out, v.iter(), pairs, |out, v| write_f32(out, v.to_f32()))
+        }
+        TypedArrayRef::F32(v) => write_all(out, v.iter(), pairs, write_f32),
+        TypedArrayRef::F64(v) => write_all(out, v.iter(), pairs, write_f64),
+        TypedArrayRef::F128(v) => {
+            write_all(out, v.iter(), pairs, |out, v| write_f64(out, v.to_f64()))
+        }
+    }
+}
+
+/// Copies compact JSON, breaking lines after each opening bracket and member.
+fn indent(out: &mut String, compact: &str) {
+    fn newline(out: &mut String, depth: usize) {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+    }
+
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = compact.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                if let Some(&close @ ('}' | ']')) = chars.peek() {
+                    out.push(close);
+                    chars.next();
+                } else {
+                    depth += 1;
+                    newline(out, depth);
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                newline(out, depth);
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(out, depth);
+            }
+            ':' => out.push_str(": "),
+            c => out.push(c),
+        }
+    }
+}
+
+fn write_display(out: &mut String, v: &impl std::fmt::Display) {
+    let _ = write!(out, "{v}");
+}
+
+fn write_str(out: &mut String, s: &str) {
+    // Serializing a string to JSON cannot fail.
+    out.push_str(&serde_json::to_string(s).unwrap_or_default());
+}
+
+fn write_f32(out: &mut String, v: f32) {
+    match serde_json::to_string(&v) {
+        Ok(json) => out.push_str(&json),
+        Err(_) => out.push_str("null"),
+    }
+}
+
+fn write_f64(out: &mut String, v: f64) {
+    match serde_json::to_string(&v) {
+        Ok(json) => out.push_str(&json),
+        Err(_) => out.push_str("null"),
+    }
+}
+
+/// A JSON value read into a [`Value`].
+struct Json(Value);
+
+impl<'de> Deserialize<'de> for Json {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor).map(Json)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::I64(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(match i64::try_from(v) {
+            Ok(v) => Value::I64(v),
+            Err(_) => Value::U64(v),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::F64(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(Json(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut members = Vec::new();
+        while let Some((key, Json(value))) = map.next_entry::<String, Json>()? {
+            members.push((key, value));
+        }
+        Ok(object(members))
+    }
+}
+
+/// A typed array when the elements allow one, else a generic array.
+fn array(values: Vec<Value>) -> Value {
+    if values.is_empty() {
+        return Value::Array(values);
+    }
+    if values.iter().all(|v| matches!(v, Value::Bool(_))) {
+        return Value::TypedArray(TypedArray::Bool(
+            values.iter().filter_map(Value::as_bool).collect(),
+        ));
+    }
+    if values.iter().all(|v| matches!(v, Value::String(_))) {
+        return Value::TypedArray(TypedArray::String(
+            values
+                .into_iter()
+                .filter_map(|v| match v {
+                    Value::String(s) => Some(s),
+                    _ => None,
+                })
+                .collect(),
+        ));
+    }
+    if values.iter().all(|v| matches!(v, Value::I64(_))) {
+        return Value::TypedArray(TypedArray::I64(
+            values.iter().filter_map(Value::as_i64).collect(),
+        ));
+    }
+    if values
+        .iter()
+        .all(|v| matches!(v, Value::I64(0..) | Value::U64(_)))
+    {
+        return Value::TypedArray(TypedArray::U64(
+            values.iter().filter_map(Value::as_u64).collect(),
+        ));
+    }
+    // Integers alone reach this point only when negative ones and ones past
+    // `i64::MAX` are mixed, which `f64` would round.
+    if values.iter().any(|v| matches!(v, Value::F64(_)))
+        && values
+            .iter()
+            .all(|v| matches!(v, Value::I64(_) | Value::U64(_) | Value::F64(_)))
+    {
+        return Value::TypedArray(TypedArray::F64(
+            values.iter().filter_map(Value::as_f64).collect(),
+        ));
+    }
+    Value::Array(values)
+}
+
+/// A variant or matrix when the members have that form, else an object with
+/// the members in their original order.
+fn object(members: Vec<(String, Value)>) -> Value {
+    let mut keys: Vec<&str> = members.iter().map(|(k, _)| k.as_str()).collect();
+    keys.sort_unstable();
+
+    match keys[..] {
+        ["index", "value"] => {
+            if let Some(index) = member(&members, "index").and_then(Value::as_u64) {
+                let value = members
+                    .into_iter()
+                    .find(|(k, _)| k == "value")
+                    .map_or(Value::Null, |(_, v)| v);
+                return Value::Variant {
+                    index,
+                    value: Box::new(value),
+                };
+            }
+        }
+        ["extents", "layout", "value"] => {
+            if let (Some(extents), Some(layout), Some(data)) = (
+                member(&members, "extents"),
+                member(&members, "layout"),
+                member(&members, "value"),
+            ) {
+                if let Some(matrix) = matrix(extents, layout, data) {
+                    return matrix;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Value::Object(
+        members
+            .into_iter()
+            .map(|(k, v)| (Key::String(k), v))
+            .collect(),
+    )
+}
+
+fn member<'a>(members: &'a [(String, Value)], key: &str) -> Option<&'a Value> {
+    members.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+fn matrix(extents: &Value, layout: &Value, data: &Value) -> Option<Value> {
+    let layout = match layout.as_str()? {
+        "layout_right" => Layout::Right,
+        "layout_left" => Layout::Left,
+        _ => return None,
+    };
+    let extents = match extents.as_typed_array()? {
+        TypedArray::I64(e) => e
+            .iter()
+            .map(|&e| u64::try_from(e).ok())
+            .collect::<Option<Vec<_>>>()?,
+        TypedArray::U64(e) => e.clone(),
+        _ => return None,
+    };
+    let data = match data.as_typed_array()? {
+        data @ (TypedArray::I64(_) | TypedArray::U64(_) | TypedArray::F64(_)) => data.clone(),
+        _ => return None,
+    };
+    let len = extents
+        .iter()
+        .try_fold(1u64, |len, &extent| len.checked_mul(extent));
+    if len != Some(data.len() as u64) {
+        return None;
+    }
+    Some(Value::Matrix {
+        layout,
+        extents,
+        data,
+    })
+}
@@ -0,0 +1,279 @@
+// Navigating a document without decoding it: values are skipped using their
+// HEADER and SIZE.
+
+use crate::borrowed::{KeyRef, ValueRef};
+use crate::error::Error;
+use crate::header::{self, BOOL_OR_STRING, SIGNED, UNSIGNED};
+use crate::reader::{Reader, DEFAULT_MAX_DEPTH};
+use crate::value::Value;
+
+impl Reader<'_> {
+    /// Moves past one value without decoding it. Strings are not checked to
+    /// be UTF-8, nor matrix extents against their data. At the top level any
+    /// data delimiters in front of the value are skipped.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        if self.depth == 0 {
+            while self.read_delimiter() {}
+        }
+        let start = self.cursor;
+        let header = self.read_uint8()?;
+
+        match header::value_type(header) {
+            header::NULL => {}
+            header::NUMBER => {
+                self.read_numbers_ref(header, start, 1)?;
+            }
+            header::STRING => self.skip_string()?,
+            header::OBJECT => {
+                let n = self.read_count(key_size(start, header)? + 1)?;
+
+                self.enter()?;
+                for _ in 0..n {
+                    let key = self.read_key_ref(header)?;
+                    self.skip_value().map_err(|e| e.at_key(key.to_string()))?;
+                }
+                self.leave();
+            }
+            // Strings are skipped one by one, without checking them.
+            header::TYPED_ARRAY
+                if header::number_type(header) == BOOL_OR_STRING && header & 0b00100000 != 0 =>
+            {
+                let n = self.read_count(1)?;
+                for i in 0..n {
+                    self.skip_string().map_err(|e| e.at_index(i))?;
+                }
+            }
+            header::TYPED_ARRAY => {
+                self.read_typed_array_ref(header)?;
+            }
+            header::GENERIC_ARRAY => {
+                let n = self.read_count(1)?;
+
+                self.enter()?;
+                for i in 0..n {
+                    self.skip_value().map_err(|e| e.at_index(i))?;
+                }
+                self.leave();
+            }
+            header::EXTENSION => {
+                self.enter()?;
+                self.skip_extension(header)?;
+                self.leave();
+            }
+            _ => return Err(Error::invalid_header(start, header)),
+        }
+        Ok(())
+    }
+
+    fn skip_string(&mut self) -> Result<(), Error> {
+        let size = self.read_size()?;
+        self.read_bytes(size)?;
+        Ok(())
+    }
+
+    fn skip_extension(&mut self, header: u8) -> Result<(), Error> {
+        let start = self.cursor - 1;
+        match header::extension(header) {
+            0 => Err(Error::type_mismatch(
+                start,
+                "a value rather than a data delimiter",
+            )),
+            1 => {
+                self.read_compressed()?;
+                self.skip_value()
+            }
+            2 => {
+                self.read_uint8()?;
+                self.skip_value()?;
+                self.skip_value()
+            }
+            3 => {
+                let complex_header = self.read_uint8()?;
+                if header::number_type(complex_header) == BOOL_OR_STRING {
+                    return Err(Error::invalid_header(start + 1, complex_header));
+                }
+                let n = match complex_header & 0b00000111 {
+                    0 => 2,
+                    1 => {
+                        let size_offset = self.cursor;
+                        self.read_size()?
+                            .checked_mul(2)
+                            .ok_or_else(|| Error::size_overflow(size_offset))?
+                    }
+                    _ => return Err(Error::invalid_header(start + 1, complex_header)),
+                };
+                self.read_numbers_ref(complex_header, start + 1, n)?;
+                Ok(())
+            }
+            extension => Err(Error::unsupported_extension(start, extension)),
+        }
+    }
+}
+
+/// The smallest size of a key of an object with the given HEADER, which
+/// starts at `start`.
+fn key_size(start: usize, header: u8) -> Result<usize, Error> {
+    match (header::number_type(header), header::byte_count(header)) {
+        (0, _) => Ok(1),
+        (SIGNED | UNSIGNED, n @ (1 | 2 | 4 | 8 | 16)) => Ok(n),
+        (BOOL_OR_STRING, _) => Err(Error::invalid_header(start, header)),
+        _ => Err(Error::unsupported_type(start, header)),
+    }
+}
+
+/// A position in a document, pointing at one value that is only decoded on
+/// request.
+///
+/// [`get`](Lazy::get) and [`index`](Lazy::index) skip over the members in
+/// front of the one looked up using their sizes, so the cost of a lookup
+/// depends on the path rather than the size of the document.
+#[derive(Debug, Clone, Copy)]
+pub struct Lazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Lazy<'a> {
+    /// Points at the first value of `buffer`, after any data delimiters.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        let mut lazy = Lazy { buffer, offset: 0 };
+        lazy.skip_delimiters();
+        lazy
+    }
+
+    /// A reader at the value. It starts one level deep, so that a data
+    /// delimiter in place of a member is an error rather than skipped; the
+    /// delimiters in front of a top-level value are skipped on the way there.
+    fn reader(&self) -> Reader<'a> {
+        let mut reader = Reader::new(self.buffer);
+        reader.cursor = self.offset;
+        reader.depth = 1;
+        reader.set_max_depth(DEFAULT_MAX_DEPTH + 1);
+        reader
+    }
+
+    fn skip_delimiters(&mut self) {
+        while self.buffer.get(self.offset) == Some(&header::DELIMITER) {
+            self.offset += 1;
+        }
+    }
+
+    /// Byte offset of the value in the buffer.
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether the cursor has moved past the last value.
+    pub fn is_at_end(&self) -> bool {
+        self.offset == self.buffer.len()
+    }
+
+    /// The HEADER of the value.
+    pub fn header(&self) -> Result<u8, Error> {
+        self.reader().peek_uint8()
+    }
+
+    /// The encoded value.
+    pub fn as_bytes(&self) -> Result<&'a [u8], Error> {
+        let mut reader = self.reader();
+        reader.skip_value()?;
+        Ok(&self.buffer[self.offset..reader.cursor])
+    }
+
+    /// Moves to the value that follows this one, skipping any data
+    /// delimiters in between.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        let mut reader = self.reader();
+        reader.skip_value()?;
+        self.offset = reader.cursor;
+        self.skip_delimiters();
+        Ok(())
+    }
+
+    /// Looks up a member of an object. The members of an integer keyed
+    /// object are matched by the decimal form of their keys, so `"-3"` finds
+    /// the key `-3`. Returns `None` when the key is missing, and an error
+    /// when the value is not an object.
+    pub fn get(&self, key: &str) -> Result<Option<Lazy<'a>>, Error> {
+        let mut reader = self.reader();
+        let header = reader.read_uint8()?;
+        if header::value_type(header) != header::OBJECT {
+            return Err(Error::type_mismatch(self.offset, "an object"));
+        }
+        let n = reader.read_count(key_size(self.offset, header)? + 1)?;
+        // Integer keys are compared as numbers.
+        let signed = key.parse::<i128>().ok();
+        let unsigned = key.parse::<u128>().ok();
+        for _ in 0..n {
+            let member = reader.read_key_ref(header)?;
+            let found = match member {
+                KeyRef::String(s) => s == key,
+                KeyRef::I8(k) => signed == Some(k.into()),
+                KeyRef::I16(k) => signed == Some(k.into()),
+                KeyRef::I32(k) => signed == Some(k.into()),
+                KeyRef::I64(k) => signed == Some(k.into()),
+                KeyRef::I128(k) => signed == Some(k),
+                KeyRef::U8(k) => unsigned == Some(k.into()),
+                KeyRef::U16(k) => unsigned == Some(k.into()),
+                KeyRef::U32(k) => unsigned == Some(k.into()),
+                KeyRef::U64(k) => unsigned == Some(k.into()),
+                KeyRef::U128(k) => unsigned == Some(k),
+            };
+            if found {
+                return Ok(Some(Lazy {
+                    buffer: self.buffer,
+                    offset: reader.cursor,
+                }));
+            }
+            reader
+                .skip_value()
+                .map_err(|e| e.at_key(member.to_string()))?;
+        }
+        Ok(None)
+    }
+
+    /// Looks up an element of a generic array. Returns `None` when `index` is
+    /// out of bounds, and an error when the value is not a generic array.
+    ///
+    /// The elements of a typed array have no HEADER of their own to point at,
+    /// so a typed array is an error too: decode it with
+    /// [`value_ref`](Lazy::value_ref) and read the elements through its
+    /// [`TypedArrayRef`](crate::TypedArrayRef), or with [`value`](Lazy::value)
+    /// and use [`TypedArray::get`](crate::TypedArray::get).
+    pub fn index(&self, index: usize) -> Result<Option<Lazy<'a>>, Error> {
+        let mut reader = self.reader();
+        let header = reader.read_uint8()?;
+        if header::value_type(header) != header::GENERIC_ARRAY {
+            return Err(Error::type_mismatch(self.offset, "a generic array"));
+        }
+
+        let n = reader.read_count(1)?;
+        if index >= n {
+            return Ok(None);
+        }
+        for i in 0..index {
+            reader.skip_value().map_err(|e| e.at_index(i))?;
+        }
+        Ok(Some(Lazy {
+            buffer: self.buffer,
+            offset: reader.cursor,
+        }))
+    }
+
+    /// Decodes the value, borrowing from the buffer.
+    pub fn value_ref(&self) -> Result<ValueRef<'a>, Error> {
+        self.reader().read_value_ref()
+    }
+
+    /// Decodes the value.
+    pub fn value(&self) -> Result<Value, Error> {
+        self.reader().read_value()
+    }
+
+    /// Deserializes the value.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::Deserialize<'a>>(&self) -> Result<T, Error> {
+        let mut de = crate::de::Deserializer::at(self.buffer, self.offset);
+        T::deserialize(&mut de).map_err(|e| e.or_offset(self.offset))
+    }
+}
@@ -0,0 +1,52 @@
+//! Reader and writer for BEVE (Binary Efficient Versatile Encoding).
+//!
+//! See the [specification](https://github.com/beve-org/beve) for the binary
+//! layout.
+
+mod borrowed;
+#[cfg(feature = "serde")]
+mod de;
+mod error;
+mod events;
+mod ext;
+mod header;
+#[cfg(feature = "json")]
+pub mod json;
+mod lazy;
+mod reader;
+mod reflect;
+#[cfg(feature = "serde")]
+mod ser;
+mod size;
+#[cfg(feature = "serde")]
+mod stream;
+mod value;
+mod writer;
+
+pub use crate::borrowed::{Bits, KeyRef, Numbers, Strings, TypedArrayRef, ValueRef};
+#[cfg(feature = "serde")]
+pub use crate::de::{from_slice, Deserializer};
+pub use crate::error::{Error, Path, Segment};
+pub use crate::events::{Event, EventReader};
+pub use crate::ext::{Element, Layout, Matrix};
+pub use crate::lazy::Lazy;
+pub use crate::reader::{Reader, DEFAULT_MAX_DEPTH};
+pub use crate::reflect::{BeveRead, BeveWrite};
+#[cfg(feature = "serde")]
+pub use crate::ser::{to_slice, to_vec, to_writer, Serializer};
+#[cfg(feature = "serde")]
+pub use crate::stream::{IoRead, SliceRead, StreamDeserializer};
+pub use crate::value::{Key, TypedArray, Value, BF16, F128, F16};
+pub use crate::writer::Writer;
+#[cfg(feature = "derive")]
+pub use beve_derive::Beve;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::reflect::{
+        is_array, read_array, read_element, read_elements, read_field, read_members, read_null,
+        read_object, read_variant, required, unknown_variant, write_array_header, write_element,
+        write_field, write_null, write_object, write_object_header, write_tag, ReadFields, Tag,
+        WriteFields,
+    };
+}
@@ -0,0 +1,201 @@
+use crate::error::Error;
+use crate::header;
+use crate::value::{BF16, F128, F16};
+
+/// Default limit on how deeply objects, arrays and extensions may nest.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Reads BEVE values from a byte slice.
+pub struct Reader<'a> {
+    pub(crate) buffer: &'a [u8],
+    pub(crate) cursor: usize,
+    pub(crate) depth: usize,
+    /// Set once a read needed more bytes than were left.
+    short: bool,
+    max_depth: usize,
+    widen_half: bool,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buffer: &'a [u8]) -> Self {
+        Reader {
+            buffer,
+            cursor: 0,
+            depth: 0,
+            short: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            widen_half: false,
+        }
+    }
+
+    /// Limits how deeply values may nest before reading fails with
+    /// [`Error::DepthLimit`].
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Reads bfloat16 and float16 numbers as [`Value::F32`](crate::Value::F32)
+    /// and typed arrays of them as [`TypedArray::F32`](crate::TypedArray::F32).
+    /// The conversion is lossless.
+    pub fn set_widen_half(&mut self, widen: bool) {
+        self.widen_half = widen;
+    }
+
+    pub(crate) fn widen_half(&self) -> bool {
+        self.widen_half
+    }
+
+    /// Byte offset of the next value to be read.
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.cursor
+    }
+
+    /// Fails a read that needs more bytes than are left.
+    pub(crate) fn eof(&mut self) -> Error {
+        self.short = true;
+        Error::eof(self.cursor)
+    }
+
+    /// Whether a read has needed more bytes than were left, even if the
+    /// error it returned was replaced by another. A failed read does not
+    /// move the cursor, so one at the end counts too.
+    #[cfg(feature = "serde")]
+    pub(crate) fn ran_short(&self) -> bool {
+        self.short || self.cursor == self.buffer.len()
+    }
+
+    pub(crate) fn enter(&mut self) -> Result<(), Error> {
+        if self.depth >= self.max_depth {
+            return Err(Error::depth_limit(self.cursor));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Returns the next byte without consuming it.
+    pub fn peek_uint8(&self) -> Result<u8, Error> {
+        self.buffer
+            .get(self.cursor)
+            .copied()
+            .ok_or_else(|| Error::eof(self.cursor))
+    }
+
+    /// Consumes a data delimiter if one comes next.
+    pub fn read_delimiter(&mut self) -> bool {
+        if self.buffer.get(self.cursor) == Some(&header::DELIMITER) {
+            self.cursor += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if n > self.remaining() {
+            return Err(self.eof());
+        }
+        let bytes = &self.buffer[self.cursor..self.cursor + n];
+        self.cursor += n;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.read_bytes(N)?);
+        Ok(bytes)
+    }
+
+    pub fn read_uint8(&mut self) -> Result<u8, Error> {
+        let val = self.peek_uint8()?;
+        self.cursor += 1;
+        Ok(val)
+    }
+
+    pub fn read_int8(&mut self) -> Result<i8, Error> {
+        Ok(self.read_uint8()? as i8)
+    }
+
+    pub fn read_uint16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_int16(&mut self) -> Result<i16, Error> {
+        Ok(i16::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_uint32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_int32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_uint64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_int64(&mut self) -> Result<i64, Error> {
+        Ok(i64::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_uint128(&mut self) -> Result<u128, Error> {
+        Ok(u128::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_int128(&mut self) -> Result<i128, Error> {
+        Ok(i128::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_bf16(&mut self) -> Result<BF16, Error> {
+        Ok(BF16(self.read_uint16()?))
+    }
+
+    pub fn read_f16(&mut self) -> Result<F16, Error> {
+        Ok(F16(self.read_uint16()?))
+    }
+
+    pub fn read_f128(&mut self) -> Result<F128, Error> {
+        Ok(F128(self.read_uint128()?))
+    }
+
+    pub fn read_float(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_double(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads `n` booleans packed eight to a byte, the first in the most
+    /// significant bit. Padding bits in the last byte are ignored.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn read_bits(&mut self, n: usize) -> Result<Vec<bool>, Error> {
+        let bytes = self.read_bytes(n.div_ceil(8))?;
+        Ok((0..n)
+            .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+            .collect())
+    }
+
+    /// Reads a string without a HEADER: SIZE followed by UTF-8 DATA.
+    pub fn read_string(&mut self) -> Result<String, Error> {
+        self.read_str().map(str::to_string)
+    }
+
+    /// Reads a string without a HEADER, borrowed from the input.
+    pub fn read_str(&mut self) -> Result<&'a str, Error> {
+        let size = self.read_size()?;
+        let start = self.cursor;
+        let str_bytes = self.read_bytes(size)?;
+        std::str::from_utf8(str_bytes).map_err(|_| Error::invalid_utf8(start))
+    }
+}
@@ -0,0 +1,655 @@
+// Reflection-style encoding: types that write and read themselves directly,
+// without going through serde. `#[derive(Beve)]` implements these traits for
+// structs and enums.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::borrowed::{TypedArrayRef, ValueRef};
+use crate::error::Error;
+use crate::ext::Element;
+use crate::header::{self, BOOL_OR_STRING};
+use crate::reader::Reader;
+use crate::value::{Value, BF16, F128, F16};
+use crate::writer::Writer;
+
+/// Types that write themselves as one BEVE value.
+pub trait BeveWrite {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error>;
+
+    /// Writes a slice of values: numbers, booleans and strings as a typed
+    /// array, anything else as a generic array.
+    #[doc(hidden)]
+    fn write_beve_slice(items: &[Self], writer: &mut Writer<'_>) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        writer.append_uint8(header::GENERIC_ARRAY)?;
+        writer.encode_compressed(items.len() as u64)?;
+        for (i, item) in items.iter().enumerate() {
+            item.write_beve(writer).map_err(|e| e.at_index(i))?;
+        }
+        Ok(())
+    }
+}
+
+/// Types that read themselves from one BEVE value.
+pub trait BeveRead: Sized {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error>;
+
+    /// Reads a typed or generic array of values.
+    #[doc(hidden)]
+    fn read_beve_vec(reader: &mut Reader<'_>) -> Result<Vec<Self>, Error> {
+        read_generic_vec(reader)
+    }
+
+    /// The value of a struct field whose key is missing, when the field may
+    /// be left out.
+    #[doc(hidden)]
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+impl Writer<'_> {
+    /// Writes a value through its [`BeveWrite`] implementation.
+    pub fn write<T: BeveWrite + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.write_beve(self)
+    }
+}
+
+impl Reader<'_> {
+    /// Reads a value through its [`BeveRead`] implementation.
+    pub fn read<T: BeveRead>(&mut self) -> Result<T, Error> {
+        T::read_beve(self)
+    }
+}
+
+/// Skips data delimiters in front of a top-level value and returns where the
+/// value starts.
+fn value_start(reader: &mut Reader<'_>) -> usize {
+    if reader.depth == 0 {
+        while reader.read_delimiter() {}
+    }
+    reader.position()
+}
+
+fn read_generic_vec<T: BeveRead>(reader: &mut Reader<'_>) -> Result<Vec<T>, Error> {
+    let start = value_start(reader);
+    let header = reader.read_uint8()?;
+    if header::value_type(header) != header::GENERIC_ARRAY {
+        return Err(Error::type_mismatch(start, "an array"));
+    }
+    let n = reader.read_count(1)?;
+
+    reader.enter()?;
+    let mut items = Vec::with_capacity(n);
+    for i in 0..n {
+        items.push(T::read_beve(reader).map_err(|e| e.at_index(i))?);
+    }
+    reader.leave();
+    Ok(items)
+}
+
+/// Reads a typed array with `pick`, which returns `None` when the elements
+/// have another type, or falls back to a generic array.
+fn read_typed_vec<'a, T: BeveRead>(
+    reader: &mut Reader<'a>,
+    pick: impl FnOnce(TypedArrayRef<'a>) -> Option<Vec<T>>,
+) -> Result<Vec<T>, Error> {
+    let start = value_start(reader);
+    let header = reader.peek_uint8()?;
+    if header::value_type(header) != header::TYPED_ARRAY {
+        return read_generic_vec(reader);
+    }
+    reader.read_uint8()?;
+    let array = reader.read_typed_array_ref(header)?;
+    pick(array).ok_or_else(|| Error::type_mismatch(start, "a typed array of the element type"))
+}
+
+/// Writes numbers as a typed array.
+fn write_numbers<T: Element>(items: &[T], writer: &mut Writer<'_>) -> Result<(), Error> {
+    writer.append_uint8(T::ARRAY_HEADER)?;
+    writer.encode_compressed(items.len() as u64)?;
+    writer.append_numbers(items)
+}
+
+/// Any integer that fits in `T`.
+fn int<T: TryFrom<i128> + TryFrom<u128>>(value: &ValueRef<'_>) -> Option<T> {
+    match *value {
+        ValueRef::I8(v) => T::try_from(i128::from(v)).ok(),
+        ValueRef::I16(v) => T::try_from(i128::from(v)).ok(),
+        ValueRef::I32(v) => T::try_from(i128::from(v)).ok(),
+        ValueRef::I64(v) => T::try_from(i128::from(v)).ok(),
+        ValueRef::I128(v) => T::try_from(v).ok(),
+        ValueRef::U8(v) => T::try_from(u128::from(v)).ok(),
+        ValueRef::U16(v) => T::try_from(u128::from(v)).ok(),
+        ValueRef::U32(v) => T::try_from(u128::from(v)).ok(),
+        ValueRef::U64(v) => T::try_from(u128::from(v)).ok(),
+        ValueRef::U128(v) => T::try_from(v).ok(),
+        _ => None,
+    }
+}
+
+/// Any number, converted as with `as`.
+fn float(value: &ValueRef<'_>) -> Option<f64> {
+    match *value {
+        ValueRef::BF16(v) => Some(f64::from(v.to_f32())),
+        ValueRef::F16(v) => Some(f64::from(v.to_f32())),
+        ValueRef::F32(v) => Some(f64::from(v)),
+        ValueRef::F64(v) => Some(v),
+        ValueRef::F128(v) => Some(v.to_f64()),
+        ValueRef::I8(v) => Some(f64::from(v)),
+        ValueRef::I16(v) => Some(f64::from(v)),
+        ValueRef::I32(v) => Some(f64::from(v)),
+        ValueRef::I64(v) => Some(v as f64),
+        ValueRef::I128(v) => Some(v as f64),
+        ValueRef::U8(v) => Some(f64::from(v)),
+        ValueRef::U16(v) => Some(f64::from(v)),
+        ValueRef::U32(v) => Some(f64::from(v)),
+        ValueRef::U64(v) => Some(v as f64),
+        ValueRef::U128(v) => Some(v as f64),
+        _ => None,
+    }
+}
+
+macro_rules! impl_number {
+    ($($t:ty => $variant:ident, $expected:literal, |$v:ident| $convert:expr;)*) => {
+        $(
+            impl BeveWrite for $t {
+                fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+                    // A number HEADER is the typed array HEADER with another
+                    // value type.
+                    writer.append_uint8(<$t as Element>::ARRAY_HEADER & !0b111 | header::NUMBER)?;
+                    self.append(writer)
+                }
+
+                fn write_beve_slice(items: &[Self], writer: &mut Writer<'_>) -> Result<(), Error> {
+                    write_numbers(items, writer)
+                }
+            }
+
+            impl BeveRead for $t {
+                fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+                    let start = value_start(reader);
+                    let $v = reader.read_value_ref()?;
+                    $convert.ok_or_else(|| Error::type_mismatch(start, $expected))
+                }
+
+                fn read_beve_vec(reader: &mut Reader<'_>) -> Result<Vec<Self>, Error> {
+                    read_typed_vec(reader, |array| match array {
+                        TypedArrayRef::$variant(numbers) => Some(numbers.to_vec()),
+                        _ => None,
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_number! {
+    i8 => I8, "an integer that fits in i8", |v| int(&v);
+    i16 => I16, "an integer that fits in i16", |v| int(&v);
+    i32 => I32, "an integer that fits in i32", |v| int(&v);
+    i64 => I64, "an integer that fits in i64", |v| int(&v);
+    i128 => I128, "an integer that fits in i128", |v| int(&v);
+    u8 => U8, "an integer that fits in u8", |v| int(&v);
+    u16 => U16, "an integer that fits in u16", |v| int(&v);
+    u32 => U32, "an integer that fits in u32", |v| int(&v);
+    u64 => U64, "an integer that fits in u64", |v| int(&v);
+    u128 => U128, "an integer that fits in u128", |v| int(&v);
+    f32 => F32, "a number", |v| float(&v).map(|v| v as f32);
+    f64 => F64, "a number", |v| float(&v);
+    BF16 => BF16, "a bfloat16", |v| match v { ValueRef::BF16(v) => Some(v), _ => None };
+    F16 => F16, "a float16", |v| match v { ValueRef::F16(v) => Some(v), _ => None };
+    F128 => F128, "a float128", |v| match v { ValueRef::F128(v) => Some(v), _ => None };
+}
+
+impl BeveWrite for bool {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.append_uint8(if *self { 0b00011000 } else { 0b00001000 })
+    }
+
+    fn write_beve_slice(items: &[Self], writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.write_bool_array(items)
+    }
+}
+
+impl BeveRead for bool {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        let start = value_start(reader);
+        match reader.read_value_ref()? {
+            ValueRef::Bool(v) => Ok(v),
+            _ => Err(Error::type_mismatch(start, "a boolean")),
+        }
+    }
+
+    fn read_beve_vec(reader: &mut Reader<'_>) -> Result<Vec<Self>, Error> {
+        read_typed_vec(reader, |array| match array {
+            TypedArrayRef::Bool(bits) => Some(bits.iter().collect()),
+            _ => None,
+        })
+    }
+}
+
+impl BeveWrite for str {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.append_uint8(header::STRING)?;
+        writer.encode_compressed(self.len() as u64)?;
+        writer.append_bytes(self.as_bytes())
+    }
+}
+
+impl BeveWrite for String {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        self.as_str().write_beve(writer)
+    }
+
+    fn write_beve_slice(items: &[Self], writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.append_uint8(header::typed_array(BOOL_OR_STRING, 1))?;
+        writer.encode_compressed(items.len() as u64)?;
+        for s in items {
+            writer.encode_compressed(s.len() as u64)?;
+            writer.append_bytes(s.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl BeveRead for String {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        let start = value_start(reader);
+        let header = reader.read_uint8()?;
+        if header != header::STRING {
+            return Err(Error::type_mismatch(start, "a string"));
+        }
+        reader.read_string()
+    }
+
+    fn read_beve_vec(reader: &mut Reader<'_>) -> Result<Vec<Self>, Error> {
+        read_typed_vec(reader, |array| match array {
+            TypedArrayRef::String(strings) => Some(strings.iter().map(str::to_string).collect()),
+            _ => None,
+        })
+    }
+}
+
+impl<T: BeveWrite> BeveWrite for Option<T> {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        match self {
+            Some(value) => value.write_beve(writer),
+            None => writer.append_uint8(header::NULL),
+        }
+    }
+}
+
+impl<T: BeveRead> BeveRead for Option<T> {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        value_start(reader);
+        if reader.peek_uint8()? == header::NULL {
+            reader.read_uint8()?;
+            Ok(None)
+        } else {
+            T::read_beve(reader).map(Some)
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: BeveWrite + ?Sized> BeveWrite for &T {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        (**self).write_beve(writer)
+    }
+}
+
+impl<T: BeveWrite + ?Sized> BeveWrite for Box<T> {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        (**self).write_beve(writer)
+    }
+}
+
+impl<T: BeveRead> BeveRead for Box<T> {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        T::read_beve(reader).map(Box::new)
+    }
+}
+
+impl<T: BeveWrite> BeveWrite for [T] {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        T::write_beve_slice(self, writer)
+    }
+}
+
+impl<T: BeveWrite> BeveWrite for Vec<T> {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        T::write_beve_slice(self, writer)
+    }
+}
+
+impl<T: BeveRead> BeveRead for Vec<T> {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        T::read_beve_vec(reader)
+    }
+}
+
+fn write_map<'v, K, V>(
+    writer: &mut Writer<'_>,
+    len: usize,
+    members: impl Iterator<Item = (&'v K, &'v V)>,
+) -> Result<(), Error>
+where
+    K: AsRef<str> + 'v,
+    V: BeveWrite + 'v,
+{
+    write_object_header(writer, len)?;
+    for (key, value) in members {
+        write_field(writer, key.as_ref(), value)?;
+    }
+    Ok(())
+}
+
+impl<V: BeveWrite> BeveWrite for BTreeMap<String, V> {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        write_map(writer, self.len(), self.iter())
+    }
+}
+
+impl<V: BeveRead> BeveRead for BTreeMap<String, V> {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        let mut map = BTreeMap::new();
+        read_members(reader, |key, reader| {
+            map.insert(key.to_string(), read_field(reader, key)?);
+            Ok(true)
+        })?;
+        Ok(map)
+    }
+}
+
+impl<V: BeveWrite, S> BeveWrite for HashMap<String, V, S> {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        write_map(writer, self.len(), self.iter())
+    }
+}
+
+impl<V: BeveRead, S: BuildHasher + Default> BeveRead for HashMap<String, V, S> {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        let mut map = HashMap::default();
+        read_members(reader, |key, reader| {
+            map.insert(key.to_string(), read_field(reader, key)?);
+            Ok(true)
+        })?;
+        Ok(map)
+    }
+}
+
+impl BeveWrite for Value {
+    fn write_beve(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+        writer.write_value(self)
+    }
+}
+
+impl BeveRead for Value {
+    fn read_beve(reader: &mut Reader<'_>) -> Result<Self, Error> {
+        reader.read_value()
+    }
+}
+
+// Support for the code generated by `#[derive(Beve)]`.
+
+/// The members of a struct with named fields, which a flattened field writes
+/// into the enclosing object.
+#[doc(hidden)]
+pub trait WriteFields {
+    fn field_count(&self) -> usize;
+    fn write_fields(&self, writer: &mut Writer<'_>) -> Result<(), Error>;
+}
+
+/// Reads the members of a struct with named fields into `Fields`, which holds
+/// the ones found so far.
+#[doc(hidden)]
+pub trait ReadFields: Sized {
+    type Fields: Default;
+
+    /// The number of values the struct is written as with
+    /// [`Writer::set_struct_as_array`], counting flattened fields.
+    const FIELD_COUNT: usize;
+
+    /// Reads the value of `key`, or returns `false` when the key is not a
+    /// field.
+    fn read_field(
+        fields: &mut Self::Fields,
+        key: &str,
+        reader: &mut Reader<'_>,
+    ) -> Result<bool, Error>;
+
+    /// Reads the value at `index` of a struct written as an array, or returns
+    /// `false` when there is no field at that position.
+    fn read_index(
+        fields: &mut Self::Fields,
+        index: usize,
+        reader: &mut Reader<'_>,
+    ) -> Result<bool, Error>;
+
+    /// Builds the struct once every member has been read.
+    fn finish(fields: Self::Fields, offset: usize) -> Result<Self, Error>;
+}
+
+/// How an enum value names its variant.
+#[doc(hidden)]
+pub enum Tag<'a> {
+    /// A type tag holding the variant index.
+    Index(u64),
+    /// A string, for unit variants.
+    Name(&'a str),
+}
+
+#[doc(hidden)]
+pub fn write_null(writer: &mut Writer<'_>) -> Result<(), Error> {
+    writer.append_uint8(header::NULL)
+}
+
+#[doc(hidden)]
+pub fn read_null(reader: &mut Reader<'_>) -> Result<(), Error> {
+    let start = value_start(reader);
+    if reader.read_uint8()? != header::NULL {
+        return Err(Error::type_mismatch(start, "null"));
+    }
+    Ok(())
+}
+
+/// Starts a struct of `len` fields, as an object or as an array.
+#[doc(hidden)]
+pub fn write_object_header(writer: &mut Writer<'_>, len: usize) -> Result<(), Error> {
+    if writer.struct_as_array() {
+        writer.append_uint8(header::GENERIC_ARRAY)?;
+    } else {
+        writer.append_uint8(header::OBJECT)?;
+    }
+    writer.encode_compressed(len as u64)
+}
+
+#[doc(hidden)]
+pub fn write_object<T: WriteFields>(value: &T, writer: &mut Writer<'_>) -> Result<(), Error> {
+    write_object_header(writer, value.field_count())?;
+    value.write_fields(writer)
+}
+
+#[doc(hidden)]
+pub fn write_field<T: BeveWrite + ?Sized>(
+    writer: &mut Writer<'_>,
+    key: &str,
+    value: &T,
+) -> Result<(), Error> {
+    if !writer.struct_as_array() {
+        writer.encode_compressed(key.len() as u64)?;
+        writer.append_bytes(key.as_bytes())?;
+    }
+    value.write_beve(writer).map_err(|e| e.at_key(key))
+}
+
+/// Reads a struct from a string keyed object, or from a generic array holding
+/// its fields in order.
+#[doc(hidden)]
+pub fn read_object<T: ReadFields>(reader: &mut Reader<'_>) -> Result<T, Error> {
+    let offset = value_start(reader);
+    let mut fields = T::Fields::default();
+    if is_array(reader)? {
+        read_elements(reader, |index, reader| {
+            T::read_index(&mut fields, index, reader)
+        })?;
+    } else {
+        read_members(reader, |key, reader| {
+            T::read_field(&mut fields, key, reader)
+        })?;
+    }
+    T::finish(fields, offset)
+}
+
+/// Whether the next value is a generic array, as structs are written with
+/// [`Writer::set_struct_as_array`].
+#[doc(hidden)]
+pub fn is_array(reader: &mut Reader<'_>) -> Result<bool, Error> {
+    value_start(reader);
+    Ok(header::value_type(reader.peek_uint8()?) == header::GENERIC_ARRAY)
+}
+
+/// Reads a generic array, calling `element` with each index. Elements for
+/// which it returns `false` are skipped, and missing trailing elements are
+/// left to the caller, like missing keys.
+#[doc(hidden)]
+pub fn read_elements<'a>(
+    reader: &mut Reader<'a>,
+    mut element: impl FnMut(usize, &mut Reader<'a>) -> Result<bool, Error>,
+) -> Result<(), Error> {
+    let start = value_start(reader);
+    if header::value_type(reader.read_uint8()?) != header::GENERIC_ARRAY {
+        return Err(Error::type_mismatch(start, "an array"));
+    }
+    let n = reader.read_count(1)?;
+
+    reader.enter()?;
+    for i in 0..n {
+        if !element(i, reader).map_err(|e| e.at_index(i))? {
+            reader.skip_value().map_err(|e| e.at_index(i))?;
+        }
+    }
+    reader.leave();
+    Ok(())
+}
+
+/// Reads a string keyed object, calling `member` with each key. Members for
+/// which it returns `false` are skipped.
+#[doc(hidden)]
+pub fn read_members<'a>(
+    reader: &mut Reader<'a>,
+    mut member: impl FnMut(&str, &mut Reader<'a>) -> Result<bool, Error>,
+) -> Result<(), Error> {
+    let start = value_start(reader);
+    if reader.read_uint8()? != header::OBJECT {
+        return Err(Error::type_mismatch(start, "a string keyed object"));
+    }
+    // Every member takes at least a one byte key SIZE and a HEADER.
+    let n = reader.read_count(2)?;
+
+    reader.enter()?;
+    for _ in 0..n {
+        let key = reader.read_str()?;
+        if !member(key, reader)? {
+            reader.skip_value().map_err(|e| e.at_key(key))?;
+        }
+    }
+    reader.leave();
+    Ok(())
+}
+
+#[doc(hidden)]
+pub fn read_field<T: BeveRead>(reader: &mut Reader<'_>, key: &str) -> Result<T, Error> {
+    T::read_beve(reader).map_err(|e| e.at_key(key))
+}
+
+/// The value read for a field, or its [`BeveRead::missing`] value.
+#[doc(hidden)]
+pub fn required<T: BeveRead>(value: Option<T>, key: &str, offset: usize) -> Result<T, Error> {
+    value
+        .or_else(T::missing)
+        .ok_or_else(|| Error::message(format!("missing field `{key}`")).or_offset(offset))
+}
+
+#[doc(hidden)]
+pub fn write_array_header(writer: &mut Writer<'_>, len: usize) -> Result<(), Error> {
+    writer.append_uint8(header::GENERIC_ARRAY)?;
+    writer.encode_compressed(len as u64)
+}
+
+#[doc(hidden)]
+pub fn write_element<T: BeveWrite + ?Sized>(
+    writer: &mut Writer<'_>,
+    index: usize,
+    value: &T,
+) -> Result<(), Error> {
+    value.write_beve(writer).map_err(|e| e.at_index(index))
+}
+
+/// Reads a generic array of exactly `len` elements with `elements`.
+#[doc(hidden)]
+pub fn read_array<'a, T>(
+    reader: &mut Reader<'a>,
+    len: usize,
+    elements: impl FnOnce(&mut Reader<'a>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let start = value_start(reader);
+    let header = reader.read_uint8()?;
+    if header::value_type(header) != header::GENERIC_ARRAY || reader.read_size()? != len {
+        return Err(Error::type_mismatch(
+            start,
+            "an array of the expected length",
+        ));
+    }
+
+    reader.enter()?;
+    let value = elements(reader)?;
+    reader.leave();
+    Ok(value)
+}
+
+#[doc(hidden)]
+pub fn read_element<T: BeveRead>(reader: &mut Reader<'_>, index: usize) -> Result<T, Error> {
+    T::read_beve(reader).map_err(|e| e.at_index(index))
+}
+
+#[doc(hidden)]
+pub fn write_tag(writer: &mut Writer<'_>, index: u64) -> Result<(), Error> {
+    writer.append_uint8(header::EXTENSION | 1 << 3)?;
+    writer.encode_compressed(index)
+}
+
+/// Reads the tag of an enum value and hands it to `variant`, which reads the
+/// rest.
+#[doc(hidden)]
+pub fn read_variant<'a, T>(
+    reader: &mut Reader<'a>,
+    variant: impl FnOnce(Tag<'a>, &mut Reader<'a>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let start = value_start(reader);
+    let header = reader.read_uint8()?;
+    let tag = if header == header::EXTENSION | 1 << 3 {
+        Tag::Index(reader.read_compressed()?)
+    } else if header == header::STRING {
+        Tag::Name(reader.read_str()?)
+    } else {
+        return Err(Error::type_mismatch(start, "a type tag or a variant name"));
+    };
+
+    reader.enter()?;
+    let value = variant(tag, reader)?;
+    reader.leave();
+    Ok(value)
+}
+
+#[doc(hidden)]
+pub fn unknown_variant(offset: usize) -> Error {
+    Error::type_mismatch(offset, "a variant of the enum")
+}
@@ -0,0 +1,1054 @@
+// Serde serialization straight to BEVE bytes.
+
+use std::fmt::Display;
+use std::io;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::error::Error;
+use crate::header::{self, BOOL_OR_STRING, FLOAT, SIGNED, UNSIGNED};
+use crate::writer::Writer;
+
+/// Serializes Rust values as BEVE into a [`Writer`].
+pub struct Serializer<'a, 'w> {
+    writer: &'a mut Writer<'w>,
+}
+
+impl<'a, 'w> Serializer<'a, 'w> {
+    pub fn new(writer: &'a mut Writer<'w>) -> Self {
+        Serializer { writer }
+    }
+}
+
+/// Serializes `value` as BEVE.
+pub fn to_vec<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut writer = Writer::default();
+    value.serialize(&mut Serializer::new(&mut writer))?;
+    Ok(writer.into_bytes())
+}
+
+/// Serializes `value` as BEVE into `writer`. Bytes are written out as soon
+/// as no sequence or map is left open whose HEADER is still to be written.
+/// Sequences of known length are written as generic arrays, so that a long
+/// one streams through a small buffer; sequences of unknown length and maps
+/// are held in memory until they end.
+pub fn to_writer<W: io::Write, T: ?Sized + Serialize>(
+    mut writer: W,
+    value: &T,
+) -> Result<(), Error> {
+    let mut output = Writer::from_io(&mut writer);
+    value.serialize(&mut Serializer::new(&mut output))?;
+    output.finish()?;
+    Ok(())
+}
+
+/// Serializes `value` as BEVE into `bytes` and returns the number of bytes
+/// written. Nothing is allocated, except for sequences of booleans or strings
+/// and sequences that turn out not to be typed arrays. Fails with
+/// [`Error::BufferTooSmall`] giving the size needed when `bytes` is too short.
+pub fn to_slice<T: ?Sized + Serialize>(bytes: &mut [u8], value: &T) -> Result<usize, Error> {
+    let mut output = Writer::from_slice(bytes);
+    value.serialize(&mut Serializer::new(&mut output))?;
+    output.finish()
+}
+
+impl Serializer<'_, '_> {
+    fn write_number(
+        &mut self,
+        num_type: u8,
+        byte_count_index: u8,
+        bytes: &[u8],
+    ) -> Result<(), Error> {
+        self.writer
+            .append_uint8(header::number(num_type, byte_count_index))?;
+        self.writer.append_bytes(bytes)?;
+        Ok(())
+    }
+
+    fn write_str(&mut self, v: &str) -> Result<(), Error> {
+        self.writer.encode_compressed(v.len() as u64)?;
+        self.writer.append_bytes(v.as_bytes())?;
+        Ok(())
+    }
+
+    fn write_variant_tag(&mut self, variant_index: u32) -> Result<(), Error> {
+        self.writer.append_uint8(header::EXTENSION | (1 << 3))?;
+        self.writer.encode_compressed(variant_index.into())?;
+        Ok(())
+    }
+
+    fn write_container(&mut self, value_type: u8, len: usize) -> Result<(), Error> {
+        self.writer.append_uint8(value_type)?;
+        self.writer.encode_compressed(len as u64)?;
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'w> ser::Serializer for &'a mut Serializer<'b, 'w> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeq<'a, 'b, 'w>;
+    type SerializeTuple = Compound<'a, 'b, 'w>;
+    type SerializeTupleStruct = Compound<'a, 'b, 'w>;
+    type SerializeTupleVariant = Compound<'a, 'b, 'w>;
+    type SerializeMap = SerializeMap<'a, 'b, 'w>;
+    type SerializeStruct = Compound<'a, 'b, 'w>;
+    type SerializeStructVariant = Compound<'a, 'b, 'w>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.writer.append_uint8(0b00001000 | (u8::from(v) << 4))?;
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.write_number(SIGNED, 0, &v.to_le_bytes())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.write_number(SIGNED, 1, &v.to_le_bytes())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.write_number(SIGNED, 2, &v.to_le_bytes())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.write_number(SIGNED, 3, &v.to_le_bytes())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        self.write_number(SIGNED, 4, &v.to_le_bytes())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.write_number(UNSIGNED, 0, &v.to_le_bytes())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.write_number(UNSIGNED, 1, &v.to_le_bytes())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.write_number(UNSIGNED, 2, &v.to_le_bytes())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.write_number(UNSIGNED, 3, &v.to_le_bytes())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.write_number(UNSIGNED, 4, &v.to_le_bytes())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.write_number(FLOAT, 2, &v.to_le_bytes())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.write_number(FLOAT, 3, &v.to_le_bytes())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.writer.append_uint8(header::STRING)?;
+        self.write_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.writer.append_uint8(header::typed_array(UNSIGNED, 0))?;
+        self.writer.encode_compressed(v.len() as u64)?;
+        self.writer.append_bytes(v)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.writer.append_uint8(header::NULL)?;
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_variant_tag(variant_index)?;
+        self.serialize_unit()
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_variant_tag(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        // A typed array written out as it goes could not be turned into a
+        // generic one by a later element of another type.
+        if let (Some(len), true) = (len, self.writer.is_io()) {
+            let start = self.writer.len();
+            self.write_container(header::GENERIC_ARRAY, len)?;
+            return Ok(SerializeSeq {
+                start,
+                ser: self,
+                len: Some(len),
+                count: 0,
+                mode: SeqMode::Prefixed,
+                offsets: Vec::new(),
+            });
+        }
+        Ok(SerializeSeq {
+            start: self.writer.hold(),
+            ser: self,
+            len,
+            count: 0,
+            mode: SeqMode::Empty,
+            offsets: Vec::new(),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.write_container(header::GENERIC_ARRAY, len)?;
+        Ok(Compound { ser: self })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        self.write_variant_tag(variant_index)?;
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        let start = self.writer.hold();
+        if let Some(len) = len {
+            self.write_container(header::OBJECT, len)?;
+        }
+        Ok(SerializeMap {
+            ser: self,
+            start,
+            len,
+            object_header: None,
+            count: 0,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        if self.writer.struct_as_array() {
+            self.write_container(header::GENERIC_ARRAY, len)?;
+        } else {
+            self.write_container(header::OBJECT, len)?;
+        }
+        Ok(Compound { ser: self })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        self.write_variant_tag(variant_index)?;
+        self.serialize_struct(name, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Tuples, structs and their enum variants, whose length is known upfront.
+pub struct Compound<'a, 'b, 'w> {
+    ser: &'a mut Serializer<'b, 'w>,
+}
+
+impl ser::SerializeTuple for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if !self.ser.writer.struct_as_array() {
+            self.ser.write_str(key)?;
+        }
+        let start = self.ser.writer.len();
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|e| e.or_offset(start).at_key(key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if !self.ser.writer.struct_as_array() {
+            self.ser.write_str(key)?;
+        }
+        let start = self.ser.writer.len();
+        value
+            .serialize(&mut *self.ser)
+            .map_err(|e| e.or_offset(start).at_key(key))
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Maps. The HEADER depends on the type of the keys, so it is patched once
+/// the first key has been written. When the length is not known upfront the
+/// HEADER and SIZE are inserted in front of the members at the end.
+pub struct SerializeMap<'a, 'b, 'w> {
+    ser: &'a mut Serializer<'b, 'w>,
+    start: usize,
+    len: Option<usize>,
+    object_header: Option<u8>,
+    count: usize,
+}
+
+impl ser::SerializeMap for SerializeMap<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.count += 1;
+        let offset = self.ser.writer.len();
+        let object_header = key.serialize(KeySerializer {
+            ser: &mut *self.ser,
+        })?;
+        match self.object_header {
+            None => {
+                self.object_header = Some(object_header);
+                if self.len.is_some() {
+                    self.ser.writer.patch_uint8(self.start, object_header);
+                    self.ser.writer.release()?;
+                }
+                Ok(())
+            }
+            Some(expected) if expected == object_header => Ok(()),
+            Some(_) => Err(Error::type_mismatch(offset, "keys of a single type")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        if self.len.is_some_and(|len| len != self.count) {
+            return Err(
+                Error::message("map length differs from its size hint").or_offset(self.start)
+            );
+        }
+        if self.len.is_none() {
+            let mut bytes = [0; 9];
+            let mut prefix = Writer::from_slice(&mut bytes);
+            prefix.append_uint8(self.object_header.unwrap_or(header::OBJECT))?;
+            prefix.encode_compressed(self.count as u64)?;
+            self.ser.writer.insert(self.start, prefix.as_bytes());
+        }
+        if self.len.is_none() || self.object_header.is_none() {
+            self.ser.writer.release()?;
+        }
+        Ok(())
+    }
+}
+
+/// Booleans, numbers and strings that can be packed into a typed array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scalar {
+    /// Buffered as one byte per value and packed into bits at the end.
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Str,
+}
+
+impl Scalar {
+    /// The HEADER of a single value of this type. For `Bool` the value is
+    /// part of the HEADER; this is the HEADER of `false`.
+    fn header(self) -> u8 {
+        match self {
+            Scalar::Bool => 0b00001000,
+            Scalar::I8 => header::number(SIGNED, 0),
+            Scalar::I16 => header::number(SIGNED, 1),
+            Scalar::I32 => header::number(SIGNED, 2),
+            Scalar::I64 => header::number(SIGNED, 3),
+            Scalar::I128 => header::number(SIGNED, 4),
+            Scalar::U8 => header::number(UNSIGNED, 0),
+            Scalar::U16 => header::number(UNSIGNED, 1),
+            Scalar::U32 => header::number(UNSIGNED, 2),
+            Scalar::U64 => header::number(UNSIGNED, 3),
+            Scalar::U128 => header::number(UNSIGNED, 4),
+            Scalar::F32 => header::number(FLOAT, 2),
+            Scalar::F64 => header::number(FLOAT, 3),
+            Scalar::Str => header::STRING,
+        }
+    }
+
+    /// The HEADER of a typed array of this type.
+    fn array_header(self) -> u8 {
+        match self {
+            Scalar::Bool => header::typed_array(BOOL_OR_STRING, 0),
+            Scalar::Str => header::typed_array(BOOL_OR_STRING, 1),
+            _ => (self.header() & !0b00000111) | header::TYPED_ARRAY,
+        }
+    }
+
+    fn width(self) -> usize {
+        header::byte_count(self.header())
+    }
+}
+
+enum SeqMode {
+    Empty,
+    Typed(Scalar),
+    Generic,
+    /// A generic array whose HEADER and SIZE have already been written.
+    Prefixed,
+}
+
+/// Sequences. Elements are written as the DATA of a typed array for as long
+/// as they are all scalars of one type; the first element that is not turns
+/// the sequence into a generic array. The HEADER and SIZE are inserted in
+/// front of the elements at the end, or written as soon as the sequence turns
+/// into a generic array when its length is known, so that the elements
+/// after that need not be held back. Over an `io::Write` a sequence of known
+/// length is a generic array from the start.
+pub struct SerializeSeq<'a, 'b, 'w> {
+    ser: &'a mut Serializer<'b, 'w>,
+    start: usize,
+    len: Option<usize>,
+    count: usize,
+    mode: SeqMode,
+    /// Start of every element of a typed string array, to re-encode them.
+    offsets: Vec<usize>,
+}
+
+impl SerializeSeq<'_, '_, '_> {
+    fn start_generic(&mut self) -> Result<(), Error> {
+        match self.len {
+            Some(len) => {
+                self.ser.write_container(header::GENERIC_ARRAY, len)?;
+                self.mode = SeqMode::Prefixed;
+                self.ser.writer.release()
+            }
+            None => {
+                self.mode = SeqMode::Generic;
+                Ok(())
+            }
+        }
+    }
+
+    /// Rewrites the typed DATA written so far as generic array elements.
+    /// Bytes from `last` on belong to one more element of type `next`.
+    fn make_generic(
+        &mut self,
+        scalar: Scalar,
+        last: usize,
+        next: Option<Scalar>,
+    ) -> Result<(), Error> {
+        let raw = self.ser.writer.split_off(self.start);
+        let (typed, rest) = raw.split_at(last - self.start);
+        self.start_generic()?;
+        let writer = &mut *self.ser.writer;
+
+        if scalar == Scalar::Str {
+            for (i, &offset) in self.offsets.iter().enumerate() {
+                let end = self.offsets.get(i + 1).copied().unwrap_or(last);
+                writer.append_uint8(header::STRING)?;
+                writer.append_bytes(&typed[offset - self.start..end - self.start])?;
+            }
+        } else if scalar == Scalar::Bool {
+            for &value in typed {
+                writer.append_uint8(scalar.header() | (value << 4))?;
+            }
+        } else {
+            for element in typed.chunks(scalar.width()) {
+                writer.append_uint8(scalar.header())?;
+                writer.append_bytes(element)?;
+            }
+        }
+        match next {
+            Some(Scalar::Bool) => writer.append_uint8(Scalar::Bool.header() | (rest[0] << 4))?,
+            Some(next) => {
+                writer.append_uint8(next.header())?;
+                writer.append_bytes(rest)?;
+            }
+            None => {}
+        }
+
+        self.offsets = Vec::new();
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq<'_, '_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.count += 1;
+        let before = self.ser.writer.len();
+
+        let expected = match self.mode {
+            SeqMode::Generic | SeqMode::Prefixed => return value.serialize(&mut *self.ser),
+            SeqMode::Empty => None,
+            SeqMode::Typed(scalar) => Some(scalar),
+        };
+
+        match value.serialize(ScalarSerializer {
+            writer: &mut *self.ser.writer,
+        }) {
+            Ok(scalar) if expected.is_none() || expected == Some(scalar) => {
+                self.mode = SeqMode::Typed(scalar);
+                if scalar == Scalar::Str {
+                    self.offsets.push(before);
+                }
+                Ok(())
+            }
+            Ok(scalar) => self.make_generic(expected.unwrap(), before, Some(scalar)),
+            Err(ScalarError::NotScalar) => {
+                match expected {
+                    Some(expected) => self.make_generic(expected, before, None)?,
+                    None => self.start_generic()?,
+                }
+                value.serialize(&mut *self.ser)
+            }
+            Err(ScalarError::Error(err)) => Err(err),
+        }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        let value_type = match self.mode {
+            SeqMode::Prefixed if self.len == Some(self.count) => return Ok(()),
+            SeqMode::Prefixed => {
+                return Err(Error::message("sequence length differs from its size hint")
+                    .or_offset(self.start))
+            }
+            SeqMode::Typed(scalar) => scalar.array_header(),
+            SeqMode::Empty | SeqMode::Generic => header::GENERIC_ARRAY,
+        };
+        if let SeqMode::Typed(Scalar::Bool) = self.mode {
+            let values = self.ser.writer.split_off(self.start);
+            self.ser
+                .writer
+                .append_bits(values.iter().map(|&v| v != 0))?;
+        }
+        let mut bytes = [0; 9];
+        let mut prefix = Writer::from_slice(&mut bytes);
+        prefix.append_uint8(value_type)?;
+        prefix.encode_compressed(self.count as u64)?;
+        self.ser.writer.insert(self.start, prefix.as_bytes());
+        self.ser.writer.release()
+    }
+}
+
+#[derive(Debug)]
+enum ScalarError {
+    NotScalar,
+    Error(Error),
+}
+
+impl Display for ScalarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScalarError::NotScalar => f.write_str("not a scalar"),
+            ScalarError::Error(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl std::error::Error for ScalarError {}
+
+impl ser::Error for ScalarError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ScalarError::Error(Error::message(msg.to_string()))
+    }
+}
+
+impl From<Error> for ScalarError {
+    fn from(err: Error) -> Self {
+        ScalarError::Error(err)
+    }
+}
+
+/// Writes a scalar as typed array DATA, without a HEADER. Anything else is
+/// rejected with `NotScalar` before writing.
+struct ScalarSerializer<'a, 'w> {
+    writer: &'a mut Writer<'w>,
+}
+
+impl ScalarSerializer<'_, '_> {
+    fn write(self, scalar: Scalar, bytes: &[u8]) -> Result<Scalar, ScalarError> {
+        self.writer.append_bytes(bytes)?;
+        Ok(scalar)
+    }
+}
+
+impl ser::Serializer for ScalarSerializer<'_, '_> {
+    type Ok = Scalar;
+    type Error = ScalarError;
+
+    type SerializeSeq = Impossible<Scalar, ScalarError>;
+    type SerializeTuple = Impossible<Scalar, ScalarError>;
+    type SerializeTupleStruct = Impossible<Scalar, ScalarError>;
+    type SerializeTupleVariant = Impossible<Scalar, ScalarError>;
+    type SerializeMap = Impossible<Scalar, ScalarError>;
+    type SerializeStruct = Impossible<Scalar, ScalarError>;
+    type SerializeStructVariant = Impossible<Scalar, ScalarError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::Bool, &[v as u8])
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::I8, &v.to_le_bytes())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::I16, &v.to_le_bytes())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::I32, &v.to_le_bytes())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::I64, &v.to_le_bytes())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::I128, &v.to_le_bytes())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::U8, &v.to_le_bytes())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::U16, &v.to_le_bytes())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::U32, &v.to_le_bytes())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::U64, &v.to_le_bytes())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::U128, &v.to_le_bytes())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::F32, &v.to_le_bytes())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Scalar, ScalarError> {
+        self.write(Scalar::F64, &v.to_le_bytes())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Scalar, ScalarError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Scalar, ScalarError> {
+        self.writer.encode_compressed(v.len() as u64)?;
+        self.write(Scalar::Str, v.as_bytes())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_none(self) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_unit(self) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Scalar, ScalarError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Scalar, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ScalarError> {
+        Err(ScalarError::NotScalar)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Writes an object key without a HEADER: the SIZE and DATA of a string, or
+/// the DATA of an integer. Returns the object HEADER for keys of that type.
+struct KeySerializer<'a, 'b, 'w> {
+    ser: &'a mut Serializer<'b, 'w>,
+}
+
+impl KeySerializer<'_, '_, '_> {
+    fn key_must_be_a_string(&self) -> Error {
+        Error::type_mismatch(self.ser.writer.len(), "a string or integer key")
+    }
+
+    fn write(self, object_header: u8, bytes: &[u8]) -> Result<u8, Error> {
+        self.ser.writer.append_bytes(bytes)?;
+        Ok(object_header)
+    }
+}
+
+impl ser::Serializer for KeySerializer<'_, '_, '_> {
+    type Ok = u8;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<u8, Error>;
+    type SerializeTuple = Impossible<u8, Error>;
+    type SerializeTupleStruct = Impossible<u8, Error>;
+    type SerializeTupleVariant = Impossible<u8, Error>;
+    type SerializeMap = Impossible<u8, Error>;
+    type SerializeStruct = Impossible<u8, Error>;
+    type SerializeStructVariant = Impossible<u8, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<u8, Error> {
+        self.ser.write_str(v)?;
+        Ok(header::OBJECT)
+    }
+
+    fn serialize_char(self, v: char) -> Result<u8, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<u8, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<u8, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<u8, Error> {
+        self.write(header::object(SIGNED, 0), &v.to_le_bytes())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<u8, Error> {
+        self.write(header::object(SIGNED, 1), &v.to_le_bytes())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<u8, Error> {
+        self.write(header::object(SIGNED, 2), &v.to_le_bytes())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<u8, Error> {
+        self.write(header::object(SIGNED, 3), &v.to_le_bytes())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<u8, Error> {
+        self.write(header::object(SIGNED, 4), &v.to_le_bytes())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<u8, Error> {
+        self.write(header::object(UNSIGNED, 0), &v.to_le_bytes())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<u8, Error> {
+        self.write(header::object(UNSIGNED, 1), &v.to_le_bytes())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<u8, Error> {
+        self.write(header::object(UNSIGNED, 2), &v.to_le_bytes())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<u8, Error> {
+        self.write(header::object(UNSIGNED, 3), &v.to_le_bytes())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<u8, Error> {
+        self.write(header::object(UNSIGNED, 4), &v.to_le_bytes())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<u8, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(self.key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(self.key_must_be_a_string())
+    }
+}
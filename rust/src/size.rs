@@ -0,0 +1,58 @@
+// Compressed unsigned integers (SIZE).
+//
+// The first two bits give the number of bytes used (1, 2, 4 or 8) and the
+// remaining bits hold the value.
+
+use crate::error::Error;
+use crate::reader::Reader;
+use crate::writer::Writer;
+
+impl Reader<'_> {
+    /// Reads a compressed unsigned integer. The two low bits of the first
+    /// byte belong to the integer, so every form is read from its first byte.
+    pub fn read_compressed(&mut self) -> Result<u64, Error> {
+        let header = self.peek_uint8()?;
+        let n_bytes = 1 << (header & 0b00000011);
+        let bytes = self.read_bytes(n_bytes)?;
+
+        let mut le = [0u8; 8];
+        le[..n_bytes].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(le) >> 2)
+    }
+
+    /// Reads a SIZE as a length or count in memory.
+    pub(crate) fn read_size(&mut self) -> Result<usize, Error> {
+        let offset = self.cursor;
+        let n = self.read_compressed()?;
+        usize::try_from(n).map_err(|_| Error::size_overflow(offset))
+    }
+
+    /// Reads the SIZE of a container whose elements take at least
+    /// `element_size` bytes each, failing early when the input is too short
+    /// to hold them.
+    pub(crate) fn read_count(&mut self, element_size: usize) -> Result<usize, Error> {
+        let n = self.read_size()?;
+        match n.checked_mul(element_size) {
+            Some(bytes) if bytes <= self.remaining() => Ok(n),
+            _ => Err(self.eof()),
+        }
+    }
+}
+
+impl Writer<'_> {
+    /// Appends `n` as a compressed unsigned integer, using the fewest bytes
+    /// that can hold it.
+    pub fn encode_compressed(&mut self, n: u64) -> Result<(), Error> {
+        if n < 1 << 6 {
+            self.append_uint8((n << 2) as u8)
+        } else if n < 1 << 14 {
+            self.append_uint16(((n << 2) | 1) as u16)
+        } else if n < 1 << 30 {
+            self.append_uint32(((n << 2) | 2) as u32)
+        } else if n < 1 << 62 {
+            self.append_bytes(&((n << 2) | 3).to_le_bytes())
+        } else {
+            Err(Error::size_overflow(self.len()))
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// Reading a stream of top-level values, such as records separated by data
+// delimiters.
+
+use std::io;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, DeserializeOwned};
+
+use crate::de::Deserializer;
+use crate::error::Error;
+use crate::header;
+
+/// Input of a [`StreamDeserializer`] held in memory.
+pub struct SliceRead<'de> {
+    bytes: &'de [u8],
+}
+
+/// Input of a [`StreamDeserializer`] read on demand from an [`io::Read`].
+pub struct IoRead<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Bytes dropped from the front of `buffer`, for error offsets.
+    dropped: usize,
+    eof: bool,
+}
+
+impl<R: io::Read> IoRead<R> {
+    /// Reads at least as many bytes as are already buffered after `cursor`,
+    /// so that a record is decoded a logarithmic number of times.
+    fn fill(&mut self, cursor: usize) -> Result<(), Error> {
+        let want = (self.buffer.len() - cursor).max(8192);
+        let start = self.buffer.len();
+        let mut len = start;
+        self.buffer.resize(start + want, 0);
+        while len < start + want {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Ok(0) => {
+                    self.eof = true;
+                    break;
+                }
+                Ok(n) => len += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.buffer.truncate(len);
+                    return Err(err.into());
+                }
+            }
+        }
+        self.buffer.truncate(len);
+        Ok(())
+    }
+}
+
+/// Iterates over the top-level values of a buffer or reader, such as records
+/// separated by data delimiters. Delimiters are skipped; values may also
+/// follow each other directly.
+///
+/// Iteration stops after the first error.
+pub struct StreamDeserializer<R, T> {
+    source: R,
+    cursor: usize,
+    failed: bool,
+    output: PhantomData<fn() -> T>,
+}
+
+impl<'de, T: Deserialize<'de>> StreamDeserializer<SliceRead<'de>, T> {
+    pub fn from_slice(bytes: &'de [u8]) -> Self {
+        StreamDeserializer {
+            source: SliceRead { bytes },
+            cursor: 0,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<R: io::Read, T: DeserializeOwned> StreamDeserializer<IoRead<R>, T> {
+    pub fn from_reader(reader: R) -> Self {
+        StreamDeserializer {
+            source: IoRead {
+                reader,
+                buffer: Vec::new(),
+                dropped: 0,
+                eof: false,
+            },
+            cursor: 0,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+}
+
+impl<R, T> StreamDeserializer<R, T> {
+    fn fail(&mut self, err: Error) -> Option<Result<T, Error>> {
+        self.failed = true;
+        Some(Err(err))
+    }
+}
+
+impl<T> StreamDeserializer<SliceRead<'_>, T> {
+    /// Number of bytes consumed so far.
+    pub fn byte_offset(&self) -> usize {
+        self.cursor
+    }
+}
+
+impl<R, T> StreamDeserializer<IoRead<R>, T> {
+    /// Number of bytes consumed so far.
+    pub fn byte_offset(&self) -> usize {
+        self.source.dropped + self.cursor
+    }
+}
+
+fn skip_delimiters(bytes: &[u8], mut cursor: usize) -> usize {
+    while bytes.get(cursor) == Some(&header::DELIMITER) {
+        cursor += 1;
+    }
+    cursor
+}
+
+impl<'de, T: Deserialize<'de>> Iterator for StreamDeserializer<SliceRead<'de>, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        if self.failed {
+            return None;
+        }
+        let bytes = self.source.bytes;
+        self.cursor = skip_delimiters(bytes, self.cursor);
+        if self.cursor == bytes.len() {
+            return None;
+        }
+
+        let mut de = Deserializer::at(bytes, self.cursor);
+        match T::deserialize(&mut de) {
+            Ok(value) => {
+                self.cursor = de.position();
+                Some(Ok(value))
+            }
+            Err(err) => self.fail(err.or_offset(self.cursor)),
+        }
+    }
+}
+
+impl<R: io::Read, T: DeserializeOwned> Iterator for StreamDeserializer<IoRead<R>, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        if self.failed {
+            return None;
+        }
+        // Drop the records already returned once they fill half the buffer.
+        if self.cursor > 0 && self.cursor >= self.source.buffer.len() / 2 {
+            self.source.buffer.drain(..self.cursor);
+            self.source.dropped += self.cursor;
+            self.cursor = 0;
+        }
+
+        loop {
+            self.cursor = skip_delimiters(&self.source.buffer, self.cursor);
+            if self.cursor == self.source.buffer.len() {
+                if self.source.eof {
+                    return None;
+                }
+                if let Err(err) = self.source.fill(self.cursor) {
+                    return self.fail(err);
+                }
+                continue;
+            }
+
+            let mut de = Deserializer::at(&self.source.buffer, self.cursor);
+            match T::deserialize(&mut de) {
+                Ok(value) => {
+                    self.cursor = de.position();
+                    return Some(Ok(value));
+                }
+                // Deserialize impls may replace the end-of-input error with
+                // their own, so ask the reader whether it ran out instead.
+                Err(_) if de.ran_short() && !self.source.eof => {
+                    if let Err(err) = self.source.fill(self.cursor) {
+                        return self.fail(err);
+                    }
+                }
+                Err(err) => {
+                    let err = err.or_offset(self.cursor).offset_by(self.source.dropped);
+                    return self.fail(err);
+                }
+            }
+        }
+    }
+}
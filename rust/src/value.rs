@@ -0,0 +1,851 @@
+use std::fmt;
+use std::ops::Index;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::borrowed::TypedArrayRef;
+use crate::error::Error;
+use crate::ext::{Element, Layout};
+use crate::header::{self, BOOL_OR_STRING, FLOAT, SIGNED, UNSIGNED};
+use crate::reader::Reader;
+use crate::writer::Writer;
+
+/// IEEE 754 half precision float, stored as raw bits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct F16(pub u16);
+
+impl F16 {
+    /// Widens to `f32`, which represents every `F16` exactly.
+    pub fn to_f32(self) -> f32 {
+        let sign = u32::from(self.0 & 0x8000) << 16;
+        let exponent = u32::from(self.0 >> 10) & 0x1f;
+        let mantissa = u32::from(self.0 & 0x03ff);
+        match (exponent, mantissa) {
+            (0, 0) => f32::from_bits(sign),
+            // Subnormal: mantissa * 2^-24, which is a normal f32.
+            (0, _) => {
+                let magnitude = mantissa as f32 / (1 << 24) as f32;
+                if sign == 0 {
+                    magnitude
+                } else {
+                    -magnitude
+                }
+            }
+            (0x1f, _) => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+            _ => f32::from_bits(sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)),
+        }
+    }
+}
+
+/// Brain float (bfloat16), stored as raw bits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct BF16(pub u16);
+
+impl BF16 {
+    /// Widens to `f32`; bfloat16 is the upper half of an `f32`.
+    pub fn to_f32(self) -> f32 {
+        f32::from_bits(u32::from(self.0) << 16)
+    }
+}
+
+#[cfg(feature = "half")]
+mod half_impls {
+    use super::{TypedArray, Value, BF16, F16};
+
+    impl From<half::bf16> for BF16 {
+        fn from(v: half::bf16) -> Self {
+            BF16(v.to_bits())
+        }
+    }
+
+    impl From<BF16> for half::bf16 {
+        fn from(v: BF16) -> Self {
+            half::bf16::from_bits(v.0)
+        }
+    }
+
+    impl From<half::f16> for F16 {
+        fn from(v: half::f16) -> Self {
+            F16(v.to_bits())
+        }
+    }
+
+    impl From<F16> for half::f16 {
+        fn from(v: F16) -> Self {
+            half::f16::from_bits(v.0)
+        }
+    }
+
+    impl From<half::bf16> for Value {
+        fn from(v: half::bf16) -> Self {
+            Value::BF16(v.into())
+        }
+    }
+
+    impl From<half::f16> for Value {
+        fn from(v: half::f16) -> Self {
+            Value::F16(v.into())
+        }
+    }
+
+    impl From<Vec<half::bf16>> for Value {
+        fn from(v: Vec<half::bf16>) -> Self {
+            Value::TypedArray(TypedArray::BF16(v.into_iter().map(BF16::from).collect()))
+        }
+    }
+
+    impl From<Vec<half::f16>> for Value {
+        fn from(v: Vec<half::f16>) -> Self {
+            Value::TypedArray(TypedArray::F16(v.into_iter().map(F16::from).collect()))
+        }
+    }
+}
+
+/// IEEE 754 quadruple precision float, stored as raw bits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct F128(pub u128);
+
+impl F128 {
+    /// Narrows to `f64`, rounding toward zero.
+    pub fn to_f64(self) -> f64 {
+        let sign = ((self.0 >> 127) as u64) << 63;
+        let exponent = ((self.0 >> 112) & 0x7fff) as i64;
+        // The top 52 of the 112 mantissa bits.
+        let mantissa = ((self.0 >> 60) & ((1 << 52) - 1)) as u64;
+        if exponent == 0x7fff {
+            let nan = if self.0 & ((1 << 112) - 1) != 0 {
+                1 << 51
+            } else {
+                0
+            };
+            return f64::from_bits(sign | 0x7ff0_0000_0000_0000 | nan);
+        }
+        if exponent == 0 {
+            // Subnormal, far below the smallest f64.
+            return f64::from_bits(sign);
+        }
+        match exponent - 16383 + 1023 {
+            e if e >= 0x7ff => f64::from_bits(sign | 0x7ff0_0000_0000_0000),
+            e if e > 0 => f64::from_bits(sign | (e as u64) << 52 | mantissa),
+            e if e > -52 => f64::from_bits(sign | ((1 << 52) | mantissa) >> (1 - e)),
+            _ => f64::from_bits(sign),
+        }
+    }
+}
+
+/// An object key. Every key of one object has the same type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    String(String),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+}
+
+impl Key {
+    /// The HEADER of an object with keys of this type.
+    fn object_header(&self) -> u8 {
+        match self {
+            Key::String(_) => header::OBJECT,
+            Key::I8(_) => header::object(SIGNED, 0),
+            Key::I16(_) => header::object(SIGNED, 1),
+            Key::I32(_) => header::object(SIGNED, 2),
+            Key::I64(_) => header::object(SIGNED, 3),
+            Key::I128(_) => header::object(SIGNED, 4),
+            Key::U8(_) => header::object(UNSIGNED, 0),
+            Key::U16(_) => header::object(UNSIGNED, 1),
+            Key::U32(_) => header::object(UNSIGNED, 2),
+            Key::U64(_) => header::object(UNSIGNED, 3),
+            Key::U128(_) => header::object(UNSIGNED, 4),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::String(s) => f.write_str(s),
+            Key::I8(v) => write!(f, "{v}"),
+            Key::I16(v) => write!(f, "{v}"),
+            Key::I32(v) => write!(f, "{v}"),
+            Key::I64(v) => write!(f, "{v}"),
+            Key::I128(v) => write!(f, "{v}"),
+            Key::U8(v) => write!(f, "{v}"),
+            Key::U16(v) => write!(f, "{v}"),
+            Key::U32(v) => write!(f, "{v}"),
+            Key::U64(v) => write!(f, "{v}"),
+            Key::U128(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// A typed array: a sequence of values sharing one HEADER.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedArray {
+    Bool(Vec<bool>),
+    String(Vec<String>),
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    I128(Vec<i128>),
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+    U128(Vec<u128>),
+    BF16(Vec<BF16>),
+    F16(Vec<F16>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    F128(Vec<F128>),
+}
+
+/// Any BEVE value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    BF16(BF16),
+    F16(F16),
+    F32(f32),
+    F64(f64),
+    F128(F128),
+    String(String),
+    /// Object members in the order they were written.
+    Object(Vec<(Key, Value)>),
+    TypedArray(TypedArray),
+    Array(Vec<Value>),
+    /// A value tagged with the index of its alternative.
+    Variant {
+        index: u64,
+        value: Box<Value>,
+    },
+    /// A matrix with its elements in one flat typed array.
+    Matrix {
+        layout: Layout,
+        extents: Vec<u64>,
+        data: TypedArray,
+    },
+    /// A complex number: a typed array holding its real and imaginary parts.
+    Complex(TypedArray),
+    /// An array of complex numbers, with real and imaginary parts interleaved.
+    ComplexArray(TypedArray),
+}
+
+macro_rules! typed_array_dispatch {
+    ($array:expr, $v:ident => $body:expr) => {
+        match $array {
+            TypedArray::Bool($v) => $body,
+            TypedArray::String($v) => $body,
+            TypedArray::I8($v) => $body,
+            TypedArray::I16($v) => $body,
+            TypedArray::I32($v) => $body,
+            TypedArray::I64($v) => $body,
+            TypedArray::I128($v) => $body,
+            TypedArray::U8($v) => $body,
+            TypedArray::U16($v) => $body,
+            TypedArray::U32($v) => $body,
+            TypedArray::U64($v) => $body,
+            TypedArray::U128($v) => $body,
+            TypedArray::BF16($v) => $body,
+            TypedArray::F16($v) => $body,
+            TypedArray::F32($v) => $body,
+            TypedArray::F64($v) => $body,
+            TypedArray::F128($v) => $body,
+        }
+    };
+}
+
+impl TypedArray {
+    pub fn len(&self) -> usize {
+        typed_array_dispatch!(self, v => v.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies out the element at `index`.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        typed_array_dispatch!(self, v => v.get(index).cloned().map(Value::from))
+    }
+
+    /// The HEADER of this typed array.
+    pub(crate) fn header(&self) -> u8 {
+        match self {
+            TypedArray::Bool(_) => header::typed_array(BOOL_OR_STRING, 0),
+            TypedArray::String(_) => header::typed_array(BOOL_OR_STRING, 1),
+            TypedArray::I8(_) => i8::ARRAY_HEADER,
+            TypedArray::I16(_) => i16::ARRAY_HEADER,
+            TypedArray::I32(_) => i32::ARRAY_HEADER,
+            TypedArray::I64(_) => i64::ARRAY_HEADER,
+            TypedArray::I128(_) => i128::ARRAY_HEADER,
+            TypedArray::U8(_) => u8::ARRAY_HEADER,
+            TypedArray::U16(_) => u16::ARRAY_HEADER,
+            TypedArray::U32(_) => u32::ARRAY_HEADER,
+            TypedArray::U64(_) => u64::ARRAY_HEADER,
+            TypedArray::U128(_) => u128::ARRAY_HEADER,
+            TypedArray::BF16(_) => BF16::ARRAY_HEADER,
+            TypedArray::F16(_) => F16::ARRAY_HEADER,
+            TypedArray::F32(_) => f32::ARRAY_HEADER,
+            TypedArray::F64(_) => f64::ARRAY_HEADER,
+            TypedArray::F128(_) => F128::ARRAY_HEADER,
+        }
+    }
+}
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::I8(v) => Some(v.into()),
+            Value::I16(v) => Some(v.into()),
+            Value::I32(v) => Some(v.into()),
+            Value::I64(v) => Some(v),
+            Value::I128(v) => v.try_into().ok(),
+            Value::U8(v) => Some(v.into()),
+            Value::U16(v) => Some(v.into()),
+            Value::U32(v) => Some(v.into()),
+            Value::U64(v) => v.try_into().ok(),
+            Value::U128(v) => v.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::I8(v) => v.try_into().ok(),
+            Value::I16(v) => v.try_into().ok(),
+            Value::I32(v) => v.try_into().ok(),
+            Value::I64(v) => v.try_into().ok(),
+            Value::I128(v) => v.try_into().ok(),
+            Value::U8(v) => Some(v.into()),
+            Value::U16(v) => Some(v.into()),
+            Value::U32(v) => Some(v.into()),
+            Value::U64(v) => Some(v),
+            Value::U128(v) => v.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    /// Any number widened to `f64`; integers may lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::BF16(v) => Some(v.to_f32().into()),
+            Value::F16(v) => Some(v.to_f32().into()),
+            Value::F32(v) => Some(v.into()),
+            Value::F64(v) => Some(v),
+            Value::I8(v) => Some(v.into()),
+            Value::I16(v) => Some(v.into()),
+            Value::I32(v) => Some(v.into()),
+            Value::I64(v) => Some(v as f64),
+            Value::I128(v) => Some(v as f64),
+            Value::U8(v) => Some(v.into()),
+            Value::U16(v) => Some(v.into()),
+            Value::U32(v) => Some(v.into()),
+            Value::U64(v) => Some(v as f64),
+            Value::U128(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn as_typed_array(&self) -> Option<&TypedArray> {
+        match self {
+            Value::TypedArray(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// The type tag and value of a variant.
+    pub fn as_variant(&self) -> Option<(u64, &Value)> {
+        match self {
+            Value::Variant { index, value } => Some((*index, value)),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Vec<(Key, Value)>> {
+        match self {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Looks up a member of a string keyed object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?
+            .iter()
+            .find(|(k, _)| matches!(k, Key::String(s) if s == key))
+            .map(|(_, v)| v)
+    }
+
+    /// Looks up an element of a generic array. Use [`TypedArray::get`] for
+    /// typed arrays.
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        self.as_array()?.get(index)
+    }
+}
+
+/// `value["key"]` returns `Value::Null` when the key is missing or `value` is
+/// not a string keyed object.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// `value[i]` returns `Value::Null` when `i` is out of bounds or `value` is not
+/// a generic array.
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+macro_rules! impl_from {
+    ($($t:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$t> for Value {
+                fn from(v: $t) -> Self {
+                    Value::$variant(v)
+                }
+            }
+
+            impl From<$t> for Key {
+                fn from(v: $t) -> Self {
+                    Key::$variant(v)
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128,
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128,
+    String => String,
+}
+
+macro_rules! impl_from_vec {
+    ($($t:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$t> for Value {
+                fn from(v: $t) -> Self {
+                    Value::$variant(v)
+                }
+            }
+
+            impl From<Vec<$t>> for Value {
+                fn from(v: Vec<$t>) -> Self {
+                    Value::TypedArray(TypedArray::$variant(v))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_vec! {
+    bool => Bool, BF16 => BF16, F16 => F16, f32 => F32, f64 => F64, F128 => F128,
+}
+
+macro_rules! impl_from_typed_vec {
+    ($($t:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<Vec<$t>> for Value {
+                fn from(v: Vec<$t>) -> Self {
+                    Value::TypedArray(TypedArray::$variant(v))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_typed_vec! {
+    String => String,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128,
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128,
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.to_string())
+    }
+}
+
+impl From<&str> for Key {
+    fn from(v: &str) -> Self {
+        Key::String(v.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(v: Vec<Value>) -> Self {
+        Value::Array(v)
+    }
+}
+
+impl From<TypedArray> for Value {
+    fn from(v: TypedArray) -> Self {
+        Value::TypedArray(v)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+impl Reader<'_> {
+    /// Reads one value. At the top level any data delimiters in front of the
+    /// value are skipped.
+    pub fn read_value(&mut self) -> Result<Value, Error> {
+        if self.depth == 0 {
+            while self.read_delimiter() {}
+        }
+        let start = self.cursor;
+        let header = self.read_uint8()?;
+
+        match header::value_type(header) {
+            header::NULL => {
+                let is_bool = (header & 0b00001000) >> 3;
+                if is_bool > 0 {
+                    let value = (header & 0b11110000) >> 4 > 0;
+                    Ok(Value::Bool(value))
+                } else {
+                    Ok(Value::Null)
+                }
+            }
+            header::NUMBER => {
+                let num_type = header::number_type(header);
+                let byte_count = header::byte_count(header);
+
+                if num_type == FLOAT {
+                    // bfloat16 uses a BYTE COUNT of 1 although it takes 2 bytes.
+                    match byte_count {
+                        1 if self.widen_half() => Ok(Value::F32(self.read_bf16()?.to_f32())),
+                        1 => Ok(Value::BF16(self.read_bf16()?)),
+                        2 if self.widen_half() => Ok(Value::F32(self.read_f16()?.to_f32())),
+                        2 => Ok(Value::F16(self.read_f16()?)),
+                        4 => Ok(Value::F32(self.read_float()?)),
+                        8 => Ok(Value::F64(self.read_double()?)),
+                        16 => Ok(Value::F128(self.read_f128()?)),
+                        _ => Err(Error::unsupported_type(start, header)),
+                    }
+                } else if num_type == SIGNED {
+                    match byte_count {
+                        1 => Ok(Value::I8(self.read_int8()?)),
+                        2 => Ok(Value::I16(self.read_int16()?)),
+                        4 => Ok(Value::I32(self.read_int32()?)),
+                        8 => Ok(Value::I64(self.read_int64()?)),
+                        16 => Ok(Value::I128(self.read_int128()?)),
+                        _ => Err(Error::unsupported_type(start, header)),
+                    }
+                } else if num_type == UNSIGNED {
+                    match byte_count {
+                        1 => Ok(Value::U8(self.read_uint8()?)),
+                        2 => Ok(Value::U16(self.read_uint16()?)),
+                        4 => Ok(Value::U32(self.read_uint32()?)),
+                        8 => Ok(Value::U64(self.read_uint64()?)),
+                        16 => Ok(Value::U128(self.read_uint128()?)),
+                        _ => Err(Error::unsupported_type(start, header)),
+                    }
+                } else {
+                    Err(Error::invalid_header(start, header))
+                }
+            }
+            header::STRING => {
+                let value = self.read_string()?;
+                Ok(Value::String(value))
+            }
+            header::OBJECT => {
+                let key_size = match (header::number_type(header), header::byte_count(header)) {
+                    (0, _) => 1,
+                    (SIGNED | UNSIGNED, n @ (1 | 2 | 4 | 8 | 16)) => n,
+                    (BOOL_OR_STRING, _) => return Err(Error::invalid_header(start, header)),
+                    _ => return Err(Error::unsupported_type(start, header)),
+                };
+                // Every member takes at least its key and a one byte HEADER.
+                let n = self.read_count(key_size + 1)?;
+
+                self.enter()?;
+                let mut members = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let key = self.read_key(header)?;
+                    let value = self.read_value().map_err(|e| e.at_key(key.to_string()))?;
+                    members.push((key, value));
+                }
+                self.leave();
+
+                Ok(Value::Object(members))
+            }
+            header::TYPED_ARRAY => Ok(Value::TypedArray(self.read_typed_array(header)?)),
+            header::GENERIC_ARRAY => {
+                let n = self.read_count(1)?;
+
+                self.enter()?;
+                let mut arr = Vec::with_capacity(n);
+                for i in 0..n {
+                    let value = self.read_value().map_err(|e| e.at_index(i))?;
+                    arr.push(value);
+                }
+                self.leave();
+
+                Ok(Value::Array(arr))
+            }
+            header::EXTENSION => {
+                self.enter()?;
+                let value = self.read_extension(header)?;
+                self.leave();
+                Ok(value)
+            }
+            _ => Err(Error::invalid_header(start, header)),
+        }
+    }
+
+    /// Reads one key of an object with the given HEADER.
+    pub(crate) fn read_key(&mut self, header: u8) -> Result<Key, Error> {
+        Ok(self.read_key_ref(header)?.to_key())
+    }
+
+    /// Reads the SIZE and DATA of a typed array whose HEADER has already been
+    /// read.
+    pub(crate) fn read_typed_array(&mut self, header: u8) -> Result<TypedArray, Error> {
+        let array = self.read_typed_array_ref(header)?;
+        Ok(self.copy_typed_array(array))
+    }
+
+    /// Copies a typed array, widening half precision floats when asked to.
+    pub(crate) fn copy_typed_array(&self, array: TypedArrayRef<'_>) -> TypedArray {
+        match array {
+            TypedArrayRef::BF16(v) if self.widen_half() => {
+                TypedArray::F32(v.iter().map(BF16::to_f32).collect())
+            }
+            TypedArrayRef::F16(v) if self.widen_half() => {
+                TypedArray::F32(v.iter().map(F16::to_f32).collect())
+            }
+            array => array.to_typed_array(),
+        }
+    }
+}
+
+impl Writer<'_> {
+    pub fn write_value(&mut self, value: &Value) -> Result<(), Error> {
+        macro_rules! write_number {
+            ($num_type:expr, $byte_count_index:expr, $v:expr) => {{
+                self.append_uint8(header::number($num_type, $byte_count_index))?;
+                self.append_bytes(&$v.to_le_bytes())?;
+            }};
+        }
+
+        match value {
+            Value::Null => self.append_uint8(header::NULL)?,
+            Value::Bool(true) => self.append_uint8(0b00011000)?,
+            Value::Bool(false) => self.append_uint8(0b00001000)?,
+            Value::I8(v) => write_number!(SIGNED, 0, v),
+            Value::I16(v) => write_number!(SIGNED, 1, v),
+            Value::I32(v) => write_number!(SIGNED, 2, v),
+            Value::I64(v) => write_number!(SIGNED, 3, v),
+            Value::I128(v) => write_number!(SIGNED, 4, v),
+            Value::U8(v) => write_number!(UNSIGNED, 0, v),
+            Value::U16(v) => write_number!(UNSIGNED, 1, v),
+            Value::U32(v) => write_number!(UNSIGNED, 2, v),
+            Value::U64(v) => write_number!(UNSIGNED, 3, v),
+            Value::U128(v) => write_number!(UNSIGNED, 4, v),
+            Value::BF16(v) => write_number!(FLOAT, 0, v.0),
+            Value::F16(v) => write_number!(FLOAT, 1, v.0),
+            Value::F32(v) => write_number!(FLOAT, 2, v),
+            Value::F64(v) => write_number!(FLOAT, 3, v),
+            Value::F128(v) => write_number!(FLOAT, 4, v.0),
+            Value::String(s) => {
+                self.append_uint8(header::STRING)?;
+                self.encode_compressed(s.len() as u64)?;
+                self.append_bytes(s.as_bytes())?;
+            }
+            Value::Object(members) => {
+                let object_header = match members.first() {
+                    Some((key, _)) => key.object_header(),
+                    None => header::OBJECT,
+                };
+                // Checked up front, so that nothing is written on a mismatch.
+                if members
+                    .iter()
+                    .any(|(key, _)| key.object_header() != object_header)
+                {
+                    return Err(Error::type_mismatch(self.len(), "keys of a single type"));
+                }
+                self.append_uint8(object_header)?;
+                self.encode_compressed(members.len() as u64)?;
+                for (key, val) in members {
+                    self.write_key(key)?;
+                    self.write_value(val)
+                        .map_err(|e| e.at_key(key.to_string()))?;
+                }
+            }
+            Value::TypedArray(arr) => self.write_typed_array(arr)?,
+            Value::Array(arr) => {
+                self.append_uint8(header::GENERIC_ARRAY)?;
+                self.encode_compressed(arr.len() as u64)?;
+                for (i, val) in arr.iter().enumerate() {
+                    self.write_value(val).map_err(|e| e.at_index(i))?;
+                }
+            }
+            Value::Variant { index, value } => {
+                self.append_uint8(header::EXTENSION | (1 << 3))?;
+                self.encode_compressed(*index)?;
+                self.write_value(value)?;
+            }
+            Value::Matrix {
+                layout,
+                extents,
+                data,
+            } => {
+                if let TypedArray::Bool(_) | TypedArray::String(_) = data {
+                    return Err(Error::type_mismatch(
+                        self.len(),
+                        "a typed array of numerical data",
+                    ));
+                }
+                self.write_matrix_header(*layout, extents, data.len())?;
+                self.write_typed_array(data)?;
+            }
+            Value::Complex(parts) | Value::ComplexArray(parts) => {
+                let is_array = matches!(value, Value::ComplexArray(_));
+                let valid = match parts {
+                    TypedArray::Bool(_) | TypedArray::String(_) => false,
+                    parts if is_array => parts.len() % 2 == 0,
+                    parts => parts.len() == 2,
+                };
+                if !valid {
+                    return Err(Error::type_mismatch(
+                        self.len(),
+                        "pairs of real and imaginary parts",
+                    ));
+                }
+                self.append_uint8(header::EXTENSION | (3 << 3))?;
+                let complex_header = parts.header() & !0b00000111;
+                if is_array {
+                    self.append_uint8(complex_header | 1)?;
+                    self.encode_compressed(parts.len() as u64 / 2)?;
+                } else {
+                    self.append_uint8(complex_header)?;
+                }
+                self.append_typed_data(parts)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an object key without a HEADER.
+    fn write_key(&mut self, key: &Key) -> Result<(), Error> {
+        match key {
+            Key::String(s) => {
+                self.encode_compressed(s.len() as u64)?;
+                self.append_bytes(s.as_bytes())
+            }
+            Key::I8(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::I16(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::I32(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::I64(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::I128(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::U8(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::U16(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::U32(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::U64(v) => self.append_bytes(&v.to_le_bytes()),
+            Key::U128(v) => self.append_bytes(&v.to_le_bytes()),
+        }
+    }
+
+    /// Writes a boolean typed array, with the values packed as single bits.
+    pub fn write_bool_array(&mut self, values: &[bool]) -> Result<(), Error> {
+        self.append_uint8(header::typed_array(BOOL_OR_STRING, 0))?;
+        self.encode_compressed(values.len() as u64)?;
+        self.append_bits(values.iter().copied())
+    }
+
+    pub(crate) fn write_typed_array(&mut self, array: &TypedArray) -> Result<(), Error> {
+        self.append_uint8(array.header())?;
+        self.encode_compressed(array.len() as u64)?;
+        self.append_typed_data(array)
+    }
+
+    /// Appends the DATA of a typed array, without its HEADER and SIZE.
+    pub(crate) fn append_typed_data(&mut self, array: &TypedArray) -> Result<(), Error> {
+        match array {
+            TypedArray::Bool(arr) => self.append_bits(arr.iter().copied()),
+            TypedArray::String(arr) => {
+                for s in arr {
+                    self.encode_compressed(s.len() as u64)?;
+                    self.append_bytes(s.as_bytes())?;
+                }
+                Ok(())
+            }
+            TypedArray::I8(arr) => self.append_numbers(arr),
+            TypedArray::I16(arr) => self.append_numbers(arr),
+            TypedArray::I32(arr) => self.append_numbers(arr),
+            TypedArray::I64(arr) => self.append_numbers(arr),
+            TypedArray::I128(arr) => self.append_numbers(arr),
+            TypedArray::U8(arr) => self.append_numbers(arr),
+            TypedArray::U16(arr) => self.append_numbers(arr),
+            TypedArray::U32(arr) => self.append_numbers(arr),
+            TypedArray::U64(arr) => self.append_numbers(arr),
+            TypedArray::U128(arr) => self.append_numbers(arr),
+            TypedArray::BF16(arr) => self.append_numbers(arr),
+            TypedArray::F16(arr) => self.append_numbers(arr),
+            TypedArray::F32(arr) => self.append_numbers(arr),
+            TypedArray::F64(arr) => self.append_numbers(arr),
+            TypedArray::F128(arr) => self.append_numbers(arr),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::io;
+
+use crate::error::Error;
+use crate::header;
+
+/// Size of the staging buffer of a writer over an `io::Write`.
+const IO_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Writes BEVE values into a growable byte buffer, a caller-provided slice or
+/// an [`io::Write`].
+pub struct Writer<'a> {
+    /// Bytes written and not handed on to an `io::Write` yet. Unused when
+    /// writing into a slice.
+    buffer: Vec<u8>,
+    output: Output<'a>,
+    /// Bytes already handed on to an `io::Write`.
+    flushed: usize,
+    /// Containers whose HEADER is still to be patched or inserted. Nothing is
+    /// flushed while there are any.
+    holds: usize,
+    struct_as_array: bool,
+}
+
+enum Output<'a> {
+    Vec,
+    /// `len` keeps counting past the end of `bytes`, so that the size needed
+    /// can be reported. Serde packs boolean sequences only once they end, so
+    /// the output can shrink, and `peak` is the most it held.
+    Slice {
+        bytes: &'a mut [u8],
+        len: usize,
+        peak: usize,
+    },
+    Io(&'a mut dyn io::Write),
+}
+
+impl Default for Writer<'_> {
+    fn default() -> Self {
+        Writer::new(256)
+    }
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(size: usize) -> Self {
+        let size = if size == 0 { 256 } else { size };
+        Writer {
+            buffer: Vec::with_capacity(size),
+            output: Output::Vec,
+            flushed: 0,
+            holds: 0,
+            struct_as_array: false,
+        }
+    }
+
+    /// Writes into `bytes` without allocating. Writing carries on past the
+    /// end of the slice, only counting bytes, and [`finish`](Writer::finish)
+    /// then fails with [`Error::BufferTooSmall`] giving the size needed.
+    pub fn from_slice(bytes: &'a mut [u8]) -> Self {
+        Writer {
+            buffer: Vec::new(),
+            output: Output::Slice {
+                bytes,
+                len: 0,
+                peak: 0,
+            },
+            flushed: 0,
+            holds: 0,
+            struct_as_array: false,
+        }
+    }
+
+    /// Writes to `sink` through a small buffer. Call [`finish`](Writer::finish)
+    /// or [`flush`](Writer::flush) to write out the buffered bytes.
+    ///
+    /// Values serialized through serde whose HEADER depends on what follows
+    /// are kept in memory until they end: sequences of unknown length, maps
+    /// and any sequence or map inside them. Sequences of known length are
+    /// written as generic arrays and stream through.
+    pub fn from_io<W: io::Write>(sink: &'a mut W) -> Self {
+        Writer {
+            buffer: Vec::with_capacity(IO_BUFFER_SIZE),
+            output: Output::Io(sink),
+            flushed: 0,
+            holds: 0,
+            struct_as_array: false,
+        }
+    }
+}
+
+impl Writer<'_> {
+    /// Writes structs as generic arrays of their field values, in field
+    /// order, instead of string keyed objects. Readers match the values to
+    /// fields by position, so both sides must agree on the fields. Values
+    /// past the last field are skipped; missing values are an error unless
+    /// their fields have defaults.
+    pub fn set_struct_as_array(&mut self, as_array: bool) {
+        self.struct_as_array = as_array;
+    }
+
+    pub(crate) fn struct_as_array(&self) -> bool {
+        self.struct_as_array
+    }
+
+    /// The bytes written so far. For a writer over an `io::Write` only those
+    /// not written out yet, and for a writer over a slice only those that
+    /// fit.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.output {
+            Output::Slice { bytes, len, .. } => &bytes[..(*len).min(bytes.len())],
+            Output::Vec | Output::Io(_) => &self.buffer,
+        }
+    }
+
+    /// Number of bytes written so far.
+    pub fn len(&self) -> usize {
+        match &self.output {
+            Output::Slice { len, .. } => *len,
+            Output::Vec | Output::Io(_) => self.flushed + self.buffer.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Marks the position of a container whose HEADER will be patched or
+    /// inserted later, and returns it.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn hold(&mut self) -> usize {
+        self.holds += 1;
+        self.len()
+    }
+
+    /// Whether bytes are written out to an `io::Write` once nothing is held,
+    /// after which they can no longer be rewritten.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn is_io(&self) -> bool {
+        matches!(self.output, Output::Io(_))
+    }
+
+    /// Ends the innermost [`hold`](Writer::hold).
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn release(&mut self) -> Result<(), Error> {
+        self.holds -= 1;
+        if self.holds == 0 && self.buffer.len() >= IO_BUFFER_SIZE {
+            self.flush_buffer()?;
+        }
+        Ok(())
+    }
+
+    /// Inserts `bytes` at position `at`, shifting everything written after it.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn insert(&mut self, at: usize, bytes: &[u8]) {
+        match &mut self.output {
+            Output::Slice {
+                bytes: slice,
+                len,
+                peak,
+            } => {
+                let end = *len + bytes.len();
+                // Past the end of the slice the content no longer matters.
+                if end <= slice.len() {
+                    slice.copy_within(at..*len, at + bytes.len());
+                    slice[at..at + bytes.len()].copy_from_slice(bytes);
+                }
+                *len = end;
+                *peak = (*peak).max(end);
+            }
+            Output::Vec | Output::Io(_) => {
+                let at = at - self.flushed;
+                self.buffer.splice(at..at, bytes.iter().copied());
+            }
+        }
+    }
+
+    /// Overwrites the byte already written at position `at`.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn patch_uint8(&mut self, at: usize, value: u8) {
+        match &mut self.output {
+            Output::Slice { bytes, .. } => {
+                if let Some(byte) = bytes.get_mut(at) {
+                    *byte = value;
+                }
+            }
+            Output::Vec | Output::Io(_) => self.buffer[at - self.flushed] = value,
+        }
+    }
+
+    /// Removes and returns everything written from position `at` on.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn split_off(&mut self, at: usize) -> Vec<u8> {
+        match &mut self.output {
+            Output::Slice { bytes, len, .. } => {
+                let mut tail = bytes[at.min(bytes.len())..(*len).min(bytes.len())].to_vec();
+                tail.resize(*len - at, 0);
+                *len = at;
+                tail
+            }
+            Output::Vec | Output::Io(_) => self.buffer.split_off(at - self.flushed),
+        }
+    }
+
+    /// Consumes the writer and returns the bytes [`as_bytes`](Writer::as_bytes)
+    /// would.
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self.output {
+            Output::Vec => self.buffer,
+            _ => self.as_bytes().to_vec(),
+        }
+    }
+
+    /// Writes out the buffered bytes and flushes the `io::Write`, unless a
+    /// container written through serde is still open. Does nothing for other
+    /// writers.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.holds > 0 {
+            return Ok(());
+        }
+        self.flush_buffer()?;
+        if let Output::Io(sink) = &mut self.output {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush_buffer(&mut self) -> Result<(), Error> {
+        if let Output::Io(sink) = &mut self.output {
+            sink.write_all(&self.buffer)?;
+            self.flushed += self.buffer.len();
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    /// Finishes writing and returns the number of bytes written. Flushes a
+    /// writer over an `io::Write`, and fails with [`Error::BufferTooSmall`]
+    /// when the output did not fit in the slice of a writer over one.
+    pub fn finish(mut self) -> Result<usize, Error> {
+        if let Output::Slice { bytes, peak, .. } = &self.output {
+            if *peak > bytes.len() {
+                return Err(Error::BufferTooSmall { needed: *peak });
+            }
+        }
+        self.flush()?;
+        Ok(self.len())
+    }
+
+    pub fn append_uint8(&mut self, value: u8) -> Result<(), Error> {
+        self.append_bytes(&[value])
+    }
+
+    pub fn append_uint16(&mut self, value: u16) -> Result<(), Error> {
+        self.append_bytes(&value.to_le_bytes())
+    }
+
+    pub fn append_uint32(&mut self, value: u32) -> Result<(), Error> {
+        self.append_bytes(&value.to_le_bytes())
+    }
+
+    pub fn append_uint64(&mut self, value: u64) -> Result<(), Error> {
+        self.append_bytes(&value.to_le_bytes())
+    }
+
+    /// Appends booleans packed eight to a byte, the first in the most
+    /// significant bit. The last byte is padded with zero bits.
+    pub(crate) fn append_bits<I: IntoIterator<Item = bool>>(
+        &mut self,
+        bits: I,
+    ) -> Result<(), Error> {
+        let mut byte = 0u8;
+        let mut n = 0;
+        for bit in bits {
+            byte |= (bit as u8) << (7 - n);
+            n += 1;
+            if n == 8 {
+                self.append_uint8(byte)?;
+                byte = 0;
+                n = 0;
+            }
+        }
+        if n > 0 {
+            self.append_uint8(byte)?;
+        }
+        Ok(())
+    }
+
+    /// Appends a data delimiter, marking the end of a record in a stream of
+    /// values.
+    pub fn write_delimiter(&mut self) -> Result<(), Error> {
+        self.append_uint8(header::DELIMITER)
+    }
+
+    /// Appends raw bytes, without a HEADER.
+    pub fn append_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        match &mut self.output {
+            Output::Vec => self.buffer.extend_from_slice(bytes),
+            Output::Slice {
+                bytes: slice,
+                len,
+                peak,
+            } => {
+                let end = *len + bytes.len();
+                if let Some(dest) = slice.get_mut(*len..end) {
+                    dest.copy_from_slice(bytes);
+                }
+                *len = end;
+                *peak = (*peak).max(end);
+            }
+            Output::Io(_) => {
+                if self.holds == 0 && self.buffer.len() + bytes.len() > IO_BUFFER_SIZE {
+                    self.flush_buffer()?;
+                    // Large blocks, such as the DATA of a typed array, skip
+                    // the buffer.
+                    if bytes.len() >= IO_BUFFER_SIZE {
+                        if let Output::Io(sink) = &mut self.output {
+                            sink.write_all(bytes)?;
+                        }
+                        self.flushed += bytes.len();
+                        return Ok(());
+                    }
+                }
+                self.buffer.extend_from_slice(bytes);
+            }
+        }
+        Ok(())
+    }
+}
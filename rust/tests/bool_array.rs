@@ -0,0 +1,50 @@
+use beve::{Reader, TypedArray, Value, Writer};
+
+const BITS: [bool; 9] = [true, false, true, true, false, false, false, true, true];
+
+/// HEADER, SIZE 9, then the bits first to last from the most significant
+/// bit, with the last byte padded with zeros.
+const ENCODED: [u8; 4] = [0x1c, 9 << 2, 0b1011_0001, 0b1000_0000];
+
+#[test]
+fn write_packs_msb_first() {
+    let mut writer = Writer::new(0);
+    writer.write_bool_array(&BITS).unwrap();
+    assert_eq!(writer.as_bytes(), ENCODED);
+
+    let mut writer = Writer::new(0);
+    writer
+        .write_value(&Value::TypedArray(TypedArray::Bool(BITS.to_vec())))
+        .unwrap();
+    assert_eq!(writer.as_bytes(), ENCODED);
+}
+
+#[test]
+fn read_unpacks_msb_first() {
+    let value = Reader::new(&ENCODED).read_value().unwrap();
+    assert_eq!(value, Value::TypedArray(TypedArray::Bool(BITS.to_vec())));
+}
+
+#[test]
+fn whole_bytes_have_no_padding() {
+    let mut writer = Writer::new(0);
+    writer.write_bool_array(&[true; 8]).unwrap();
+    assert_eq!(writer.as_bytes(), [0x1c, 8 << 2, 0xff]);
+
+    let mut writer = Writer::new(0);
+    writer.write_bool_array(&[]).unwrap();
+    assert_eq!(writer.as_bytes(), [0x1c, 0]);
+}
+
+#[test]
+fn truncated_bits() {
+    assert!(Reader::new(&ENCODED[..3]).read_value().is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_packs_msb_first() {
+    let bytes = beve::to_vec(&BITS.to_vec()).unwrap();
+    assert_eq!(bytes, ENCODED);
+    assert_eq!(beve::from_slice::<Vec<bool>>(&bytes).unwrap(), BITS);
+}
@@ -0,0 +1,64 @@
+use beve::{Reader, TypedArray, TypedArrayRef, Value, ValueRef, Writer};
+
+/// Writes `values` as a typed array `pad` bytes into a buffer aligned for
+/// `u64`, and returns the buffer and where the DATA starts.
+fn aligned_buffer(pad: usize, values: &[f64]) -> (Vec<u64>, usize) {
+    let mut writer = Writer::new(0);
+    writer
+        .write_value(&Value::TypedArray(TypedArray::F64(values.to_vec())))
+        .unwrap();
+    let encoded = writer.into_bytes();
+    let mut words = vec![0u64; (pad + encoded.len()).div_ceil(8)];
+    bytemuck::cast_slice_mut::<u64, u8>(&mut words)[pad..pad + encoded.len()]
+        .copy_from_slice(&encoded);
+    // One byte of HEADER and one of SIZE.
+    (words, pad + 2)
+}
+
+#[test]
+fn numbers_in_place_or_copied() {
+    let values = [1.5, -2.25, 1e300, f64::MIN_POSITIVE];
+    for pad in 0..8 {
+        let (words, data_start) = aligned_buffer(pad, &values);
+        let bytes: &[u8] = bytemuck::cast_slice(&words);
+        let mut reader = Reader::new(&bytes[pad..]);
+        let ValueRef::TypedArray(TypedArrayRef::F64(numbers)) = reader.read_value_ref().unwrap()
+        else {
+            panic!("not a float64 array");
+        };
+
+        // The elements are only borrowed in place when aligned, on
+        // little-endian platforms.
+        let aligned = data_start % 8 == 0 && cfg!(target_endian = "little");
+        assert_eq!(numbers.as_slice().is_some(), aligned, "pad {pad}");
+        assert_eq!(numbers.len(), values.len());
+        assert_eq!(numbers.to_vec(), values);
+        assert_eq!(numbers.iter().collect::<Vec<_>>(), values);
+        assert_eq!(numbers.get(2), Some(1e300));
+        assert_eq!(numbers.get(4), None);
+        // The end of the element would overflow, not its start.
+        assert_eq!(numbers.get(usize::MAX / 8), None);
+        assert_eq!(numbers.get(usize::MAX), None);
+        assert_eq!(
+            reader.position(),
+            2 + values.len() * 8,
+            "the reader moved past the data"
+        );
+    }
+}
+
+#[test]
+fn value_ref_matches_value() {
+    let value = Value::Array(vec![
+        Value::Null,
+        Value::String("é".into()),
+        Value::TypedArray(TypedArray::U16(vec![1, 2, 3])),
+        Value::TypedArray(TypedArray::String(vec!["a".into(), "bc".into()])),
+        Value::TypedArray(TypedArray::Bool(vec![true, false, true])),
+    ]);
+    let mut writer = Writer::new(0);
+    writer.write_value(&value).unwrap();
+    let borrowed = Reader::new(writer.as_bytes()).read_value_ref().unwrap();
+    assert_eq!(borrowed.to_value(), value);
+    assert_eq!(borrowed.get_index(1).and_then(ValueRef::as_str), Some("é"));
+}
@@ -0,0 +1,185 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use beve::{json, Key, TypedArray, Value, Writer};
+
+/// Runs the `beve` binary with `args`, feeding it `input` on stdin.
+fn beve(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_beve"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Runs `beve` and expects it to succeed, returning its stdout.
+fn success(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let output = beve(args, input);
+    assert!(
+        output.status.success(),
+        "{args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+/// Runs `beve` and expects it to fail with exit code 1, returning its
+/// message.
+fn failure(args: &[&str], input: &[u8]) -> String {
+    let output = beve(args, input);
+    assert_eq!(output.status.code(), Some(1), "{args:?}");
+    assert!(output.stdout.is_empty());
+    let message = String::from_utf8(output.stderr).unwrap();
+    assert!(message.starts_with("beve: "), "{message}");
+    message
+}
+
+fn text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap()
+}
+
+fn document() -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer
+        .write_value(&Value::Object(vec![
+            ("name".into(), Value::String("doc".into())),
+            (
+                "data".into(),
+                Value::TypedArray(TypedArray::I32(vec![1, 2, 3, -4])),
+            ),
+            (
+                "points".into(),
+                Value::Array(vec![
+                    Value::Null,
+                    Value::Object(vec![("x".into(), Value::F64(0.5))]),
+                ]),
+            ),
+            (
+                "ids".into(),
+                Value::Object(vec![(Key::U16(7), Value::String("seven".into()))]),
+            ),
+        ]))
+        .unwrap();
+    writer.into_bytes()
+}
+
+const JSON: &str =
+    r#"{"name":"doc","data":[1,2,3,-4],"points":[null,{"x":0.5}],"ids":{"7":"seven"}}"#;
+
+#[test]
+fn json_conversions() {
+    let bytes = success(&["from-json"], JSON.as_bytes());
+    assert_eq!(bytes, json::from_json(JSON).unwrap());
+    assert_eq!(text(success(&["to-json"], &bytes)), format!("{JSON}\n"));
+    assert_eq!(
+        text(success(&["dump", "-"], &bytes)),
+        format!("{}\n", json::to_json_pretty(&bytes).unwrap())
+    );
+
+    // Newline delimited JSON becomes delimited values, and back.
+    let lines = "{\"a\":1}\n{\"a\":2}\n";
+    let bytes = success(&["from-json"], lines.as_bytes());
+    assert_eq!(text(success(&["to-json"], &bytes)), lines);
+
+    failure(&["from-json"], b"{\"a\":");
+    failure(&["to-json"], &[0x02, 0x40]);
+}
+
+#[test]
+fn validate() {
+    let bytes = document();
+    assert_eq!(
+        text(success(&["validate"], &bytes)),
+        format!("ok: 1 value(s), {} bytes\n", bytes.len())
+    );
+    let message = failure(&["validate"], &bytes[..bytes.len() - 1]);
+    assert!(message.contains("unexpected end"), "{message}");
+    failure(&["validate"], &[0x07]);
+}
+
+#[test]
+fn stats() {
+    let stats = text(success(&["stats"], &document()));
+    assert!(stats.contains("values: 1\n"), "{stats}");
+    assert!(stats.contains("max depth: 4\n"), "{stats}");
+    assert!(stats.contains("  int32 array              1\n"), "{stats}");
+    let largest = stats.split("largest arrays:\n").nth(1).unwrap();
+    let paths: Vec<&str> = largest
+        .lines()
+        .map(|line| line.split_whitespace().last().unwrap())
+        .collect();
+    assert_eq!(paths, ["$.data", "$.points"]);
+
+    let top = text(success(&["stats", "--top", "1"], &document()));
+    assert_eq!(
+        top.split("largest arrays:\n").nth(1),
+        Some("           4  int32 array              $.data\n")
+    );
+}
+
+#[test]
+fn get() {
+    let bytes = document();
+    let get = |path: &str| text(success(&["get", path], &bytes));
+    assert_eq!(get("$.name"), "\"doc\"\n");
+    assert_eq!(get("name"), "\"doc\"\n");
+    assert_eq!(get("$.points[1].x"), "0.5\n");
+    assert_eq!(get("$.points[0]"), "null\n");
+    assert_eq!(get("$.ids[7]"), "\"seven\"\n");
+    assert_eq!(get("$.ids.7"), "\"seven\"\n");
+
+    // Elements of typed arrays.
+    assert_eq!(get("$.data[3]"), "-4\n");
+    assert_eq!(get("$.data[0]"), "1\n");
+    let mut element = Writer::new(0);
+    element.write_value(&Value::I32(-4)).unwrap();
+    assert_eq!(
+        success(&["get", "--raw", "$.data[3]"], &bytes),
+        element.into_bytes()
+    );
+
+    let raw = success(&["get", "--raw", "$.points"], &bytes);
+    assert_eq!(json::to_json(&raw).unwrap(), "[null,{\"x\":0.5}]");
+
+    for missing in ["$.data[4]", "$.points[2]", "$.missing", "$.ids[8]"] {
+        let message = failure(&["get", missing], &bytes);
+        assert_eq!(message, format!("beve: nothing at {missing}\n"));
+    }
+    let message = failure(&["get", "$.data[3].x"], &bytes);
+    assert_eq!(message, "beve: nothing at $.data[3].x\n");
+    let message = failure(&["get", "$.name[0]"], &bytes);
+    assert!(
+        message.starts_with("beve: nothing at $.name[0]: "),
+        "{message}"
+    );
+    let message = failure(&["get", "$.points.x"], &bytes);
+    assert!(
+        message.starts_with("beve: nothing at $.points.x: "),
+        "{message}"
+    );
+    failure(&["get", "$.data[x]"], &bytes);
+    failure(&["get", "$.data[1"], &bytes);
+}
+
+#[test]
+fn files_and_usage_errors() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("cli-document.beve");
+    std::fs::write(&path, document()).unwrap();
+    let path = path.to_str().unwrap();
+    assert_eq!(text(success(&["get", "$.name", path], b"")), "\"doc\"\n");
+    assert_eq!(text(success(&["to-json", path], b"")), format!("{JSON}\n"));
+
+    let missing = failure(&["validate", "/nonexistent/input.beve"], b"");
+    assert!(missing.contains("No such file"), "{missing}");
+
+    for args in [&["bogus"][..], &[], &["get"], &["stats", "--top", "x"]] {
+        let output = beve(args, b"");
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+    }
+}
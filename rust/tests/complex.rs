@@ -0,0 +1,37 @@
+use beve::{Reader, TypedArray, Value, Writer};
+use num_complex::Complex;
+
+#[test]
+fn complex_round_trip() {
+    let mut writer = Writer::new(0);
+    writer.write_complex(Complex::new(1.5f32, -2.0)).unwrap();
+    let value = Reader::new(writer.as_bytes()).read_value().unwrap();
+    assert_eq!(value, Value::Complex(TypedArray::F32(vec![1.5, -2.0])));
+    assert_eq!(Complex::try_from(value), Ok(Complex::new(1.5f32, -2.0)));
+}
+
+#[test]
+fn complex_array_round_trip() {
+    let values = vec![Complex::new(1i16, 2), Complex::new(-3, 4)];
+    let mut writer = Writer::new(0);
+    writer.write_complex_array(&values).unwrap();
+    let value = Reader::new(writer.as_bytes()).read_value().unwrap();
+    assert_eq!(value, Value::from(values.clone()));
+    assert_eq!(Vec::<Complex<i16>>::try_from(value), Ok(values));
+}
+
+#[test]
+fn complex_with_wrong_part_count() {
+    for parts in [vec![], vec![1.0], vec![1.0, 2.0, 3.0]] {
+        let value = Value::Complex(TypedArray::F64(parts));
+        assert_eq!(Complex::<f64>::try_from(value.clone()), Err(value));
+    }
+    let value = Value::ComplexArray(TypedArray::F64(vec![1.0, 2.0, 3.0]));
+    assert_eq!(Vec::<Complex<f64>>::try_from(value.clone()), Err(value));
+}
+
+#[test]
+fn complex_of_another_type() {
+    let value = Value::Complex(TypedArray::F32(vec![1.0, 2.0]));
+    assert_eq!(Complex::<f64>::try_from(value.clone()), Err(value));
+}
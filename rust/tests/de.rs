@@ -0,0 +1,101 @@
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Borrowed<'a> {
+    name: &'a str,
+    tags: Vec<&'a str>,
+    nickname: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape<'a> {
+    Empty,
+    Named(&'a str),
+    Pair(&'a str, u8),
+    Labelled { label: &'a str },
+}
+
+#[test]
+fn borrowed_str() {
+    let bytes = beve::to_vec(&"hi").unwrap();
+    let s: &str = beve::from_slice(&bytes).unwrap();
+    assert_eq!(s, "hi");
+}
+
+#[test]
+fn borrowed_struct() {
+    let value = Borrowed {
+        name: "hi",
+        tags: vec!["a", "bc"],
+        nickname: Some("h"),
+    };
+    let bytes = beve::to_vec(&value).unwrap();
+    assert_eq!(beve::from_slice::<Borrowed>(&bytes).unwrap(), value);
+
+    let value = Borrowed {
+        nickname: None,
+        ..value
+    };
+    let bytes = beve::to_vec(&value).unwrap();
+    assert_eq!(beve::from_slice::<Borrowed>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn borrowed_enum() {
+    for value in [
+        Shape::Empty,
+        Shape::Named("x"),
+        Shape::Pair("y", 2),
+        Shape::Labelled { label: "z" },
+    ] {
+        let bytes = beve::to_vec(&value).unwrap();
+        assert_eq!(beve::from_slice::<Shape>(&bytes).unwrap(), value);
+    }
+}
+
+#[test]
+fn unit_variant_by_name() {
+    let bytes = beve::to_vec(&"Empty").unwrap();
+    assert_eq!(beve::from_slice::<Shape>(&bytes).unwrap(), Shape::Empty);
+
+    let mut object = BTreeMap::new();
+    object.insert("Named", "w");
+    let bytes = beve::to_vec(&object).unwrap();
+    assert_eq!(
+        beve::from_slice::<Shape>(&bytes).unwrap(),
+        Shape::Named("w")
+    );
+}
+
+#[test]
+fn borrowed_tuple() {
+    let value = ("a", 1u32, Some("b"), None::<&str>);
+    let bytes = beve::to_vec(&value).unwrap();
+    assert_eq!(
+        beve::from_slice::<(&str, u32, Option<&str>, Option<&str>)>(&bytes).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn borrowed_map() {
+    let mut value = BTreeMap::new();
+    value.insert("one", "1");
+    value.insert("two", "2");
+    let bytes = beve::to_vec(&value).unwrap();
+    assert_eq!(
+        beve::from_slice::<BTreeMap<&str, &str>>(&bytes).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn borrowed_string_array() {
+    let value = vec!["x".to_string(), "yz".to_string()];
+    let bytes = beve::to_vec(&value).unwrap();
+    assert_eq!(beve::from_slice::<Vec<&str>>(&bytes).unwrap(), ["x", "yz"]);
+}
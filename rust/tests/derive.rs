@@ -0,0 +1,334 @@
+#![cfg(feature = "derive")]
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use beve::{Beve, BeveRead, BeveWrite, Key, Reader, Value, Writer};
+
+fn encode<T: BeveWrite>(value: &T) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write(value).unwrap();
+    writer.into_bytes()
+}
+
+fn decode<T: BeveRead>(bytes: &[u8]) -> T {
+    Reader::new(bytes).read().unwrap()
+}
+
+fn round_trip<T: BeveWrite + BeveRead + PartialEq + std::fmt::Debug>(value: T) {
+    assert_eq!(decode::<T>(&encode(&value)), value);
+}
+
+fn keys(bytes: &[u8]) -> Vec<String> {
+    match Reader::new(bytes).read_value().unwrap() {
+        Value::Object(members) => members.iter().map(|(k, _)| k.to_string()).collect(),
+        value => panic!("not an object: {value:?}"),
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Beve)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Beve)]
+struct Record {
+    #[beve(rename = "ID")]
+    id: u64,
+    #[beve(skip)]
+    cache: Vec<u8>,
+    #[beve(default)]
+    count: u32,
+    #[beve(flatten)]
+    point: Point,
+    values: Vec<f64>,
+    tags: Vec<String>,
+    scores: BTreeMap<String, i16>,
+    note: Option<String>,
+}
+
+fn record() -> Record {
+    Record {
+        id: 7,
+        cache: vec![1, 2, 3],
+        count: 4,
+        point: Point { x: -1, y: 2 },
+        values: vec![0.5, 1.5],
+        tags: vec!["a".into(), "b".into()],
+        scores: [("z".to_string(), 3)].into_iter().collect(),
+        note: Some("n".into()),
+    }
+}
+
+#[test]
+fn struct_attributes() {
+    let bytes = encode(&record());
+    assert_eq!(
+        keys(&bytes),
+        ["ID", "count", "x", "y", "values", "tags", "scores", "note"]
+    );
+    let read: Record = decode(&bytes);
+    assert_eq!(
+        read,
+        Record {
+            cache: Vec::new(),
+            ..record()
+        }
+    );
+}
+
+#[test]
+fn default_and_missing_fields() {
+    let object = |members: Vec<(&str, Value)>| {
+        let value = Value::Object(
+            members
+                .into_iter()
+                .map(|(k, v)| (Key::String(k.into()), v))
+                .collect(),
+        );
+        let mut writer = Writer::new(0);
+        writer.write_value(&value).unwrap();
+        writer.into_bytes()
+    };
+
+    // `count` takes its default and `note` is None when missing.
+    let bytes = object(vec![
+        ("ID", Value::U64(1)),
+        ("x", Value::I32(1)),
+        ("y", Value::I32(2)),
+        ("values", Value::Array(vec![])),
+        ("tags", Value::Array(vec![])),
+        ("scores", Value::Object(vec![])),
+    ]);
+    let read: Record = decode(&bytes);
+    assert_eq!((read.count, read.note), (0, None));
+
+    let bytes = object(vec![("x", Value::I32(1))]);
+    let err = Reader::new(&bytes).read::<Point>().unwrap_err();
+    assert!(err.to_string().contains("missing field `y`"), "{err}");
+}
+
+#[test]
+fn unknown_keys_are_skipped() {
+    let value = Value::Object(vec![
+        (
+            Key::String("extra".into()),
+            Value::Array(vec![Value::Null; 3]),
+        ),
+        (Key::String("x".into()), Value::I32(5)),
+        (
+            Key::String("nested".into()),
+            Value::Object(vec![(Key::String("x".into()), Value::I32(9))]),
+        ),
+        (Key::String("y".into()), Value::I32(6)),
+    ]);
+    let mut writer = Writer::new(0);
+    writer.write_value(&value).unwrap();
+    assert_eq!(decode::<Point>(writer.as_bytes()), Point { x: 5, y: 6 });
+}
+
+#[derive(Debug, PartialEq, Beve)]
+enum Shape {
+    Empty,
+    #[beve(rename = "Circle")]
+    Round(f64),
+    Segment(Point, Point),
+    Rect {
+        width: u32,
+        height: u32,
+    },
+}
+
+#[test]
+fn enum_variants() {
+    round_trip(Shape::Empty);
+    round_trip(Shape::Round(1.5));
+    round_trip(Shape::Segment(Point { x: 1, y: 2 }, Point::default()));
+    round_trip(Shape::Rect {
+        width: 3,
+        height: 4,
+    });
+    round_trip(vec![Shape::Empty, Shape::Round(2.0)]);
+}
+
+#[test]
+fn unit_variant_by_name() {
+    let mut writer = Writer::new(0);
+    writer.write_value(&Value::String("Empty".into())).unwrap();
+    assert_eq!(decode::<Shape>(writer.as_bytes()), Shape::Empty);
+
+    let mut writer = Writer::new(0);
+    writer.write_value(&Value::String("Other".into())).unwrap();
+    assert!(Reader::new(writer.as_bytes()).read::<Shape>().is_err());
+}
+
+/// Fields named like the locals of the generated code.
+#[derive(Debug, PartialEq, Beve)]
+enum Io {
+    Open {
+        writer: String,
+        reader: String,
+        offset: u64,
+        key: Option<String>,
+        index: u32,
+        #[beve(skip)]
+        value: u8,
+    },
+    Close(u64, u64),
+}
+
+#[test]
+fn variant_fields_named_like_locals() {
+    let open = Io::Open {
+        writer: "w".into(),
+        reader: "r".into(),
+        offset: 7,
+        key: Some("k".into()),
+        index: 2,
+        value: 0,
+    };
+    round_trip(open);
+    round_trip(Io::Close(1, 2));
+
+    let mut writer = Writer::new(0);
+    writer
+        .write_value(&Value::Variant {
+            index: 0,
+            value: Box::new(Value::Object(vec![
+                ("index".into(), Value::U32(3)),
+                ("offset".into(), Value::U64(4)),
+                ("reader".into(), Value::String("r".into())),
+            ])),
+        })
+        .unwrap();
+    let err = Reader::new(writer.as_bytes()).read::<Io>().unwrap_err();
+    assert!(err.to_string().contains("missing field `writer`"), "{err}");
+}
+
+#[derive(Debug, PartialEq, Beve)]
+struct Unit;
+
+#[derive(Debug, PartialEq, Beve)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, Beve)]
+struct Pair(u8, String);
+
+#[test]
+fn unit_and_tuple_structs() {
+    round_trip(Unit);
+    assert_eq!(encode(&Meters(2.0)), encode(&2.0f64));
+    round_trip(Meters(2.0));
+    round_trip(Pair(1, "one".into()));
+}
+
+#[derive(Debug, PartialEq, Beve)]
+struct Wrapper<T> {
+    value: T,
+    list: Vec<T>,
+}
+
+#[derive(Debug, PartialEq, Beve)]
+struct Tagged<T> {
+    id: u8,
+    #[beve(skip)]
+    marker: PhantomData<T>,
+}
+
+#[test]
+fn generic_structs() {
+    round_trip(Wrapper {
+        value: "a".to_string(),
+        list: vec!["b".to_string()],
+    });
+    round_trip(Wrapper {
+        value: Point::default(),
+        list: vec![],
+    });
+    round_trip(Tagged::<u32> {
+        id: 3,
+        marker: PhantomData,
+    });
+}
+
+#[test]
+fn depth_limit() {
+    #[derive(Debug, PartialEq, Beve)]
+    enum List {
+        Nil,
+        Cons(Box<List>),
+    }
+
+    let mut list = List::Nil;
+    for _ in 0..200 {
+        list = List::Cons(Box::new(list));
+    }
+    let bytes = encode(&list);
+    let err = Reader::new(&bytes).read::<List>().unwrap_err();
+    assert!(matches!(err, beve::Error::DepthLimit { .. }), "{err:?}");
+}
+
+#[cfg(feature = "serde")]
+mod serde_compat {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Beve)]
+    struct Both {
+        #[serde(rename = "Name")]
+        #[beve(rename = "Name")]
+        name: String,
+        values: Vec<f64>,
+        flags: Vec<bool>,
+        labels: Vec<String>,
+        nested: Vec<Inner>,
+        maybe: Option<i8>,
+        shapes: Vec<Kind>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Beve)]
+    struct Inner {
+        a: u16,
+        b: Vec<i32>,
+        c: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Beve)]
+    enum Kind {
+        Unit,
+        Newtype(u32),
+        Tuple(u8, bool),
+        Struct { v: i64 },
+    }
+
+    fn both() -> Both {
+        Both {
+            name: "both".into(),
+            values: vec![1.0, 2.5],
+            flags: vec![true, false, true],
+            labels: vec!["x".into()],
+            nested: vec![Inner {
+                a: 1,
+                b: vec![-2],
+                c: "c".into(),
+            }],
+            maybe: None,
+            shapes: vec![
+                Kind::Unit,
+                Kind::Newtype(5),
+                Kind::Tuple(1, true),
+                Kind::Struct { v: -9 },
+            ],
+        }
+    }
+
+    #[test]
+    fn same_bytes_as_serde() {
+        let derived = encode(&both());
+        assert_eq!(derived, beve::to_vec(&both()).unwrap());
+        assert_eq!(beve::from_slice::<Both>(&derived).unwrap(), both());
+        assert_eq!(decode::<Both>(&beve::to_vec(&both()).unwrap()), both());
+    }
+}
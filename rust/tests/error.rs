@@ -0,0 +1,132 @@
+use beve::{Error, Key, Reader, Segment, Value, Writer};
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(value).unwrap();
+    writer.into_bytes()
+}
+
+fn read(bytes: &[u8]) -> Error {
+    Reader::new(bytes).read_value().unwrap_err()
+}
+
+fn document(name: Value) -> Value {
+    Value::Object(vec![(
+        Key::String("results".into()),
+        Value::Array(vec![
+            Value::Null,
+            Value::Object(vec![(Key::String("name".into()), name)]),
+        ]),
+    )])
+}
+
+#[test]
+fn truncated_input() {
+    let bytes = encode(&document(Value::String("abcdef".into())));
+    let err = read(&bytes[..bytes.len() - 2]);
+    assert!(matches!(err, Error::UnexpectedEof { .. }), "{err:?}");
+    // The string content starts after its HEADER and SIZE.
+    assert_eq!(err.offset(), Some(bytes.len() - 6));
+    let path = err.path().unwrap();
+    assert_eq!(
+        path.segments().cloned().collect::<Vec<_>>(),
+        [
+            Segment::Key("results".into()),
+            Segment::Index(1),
+            Segment::Key("name".into())
+        ]
+    );
+    assert_eq!(path.to_string(), "$.results[1].name");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "unexpected end of input at byte {} ($.results[1].name)",
+            bytes.len() - 6
+        )
+    );
+
+    for len in 0..bytes.len() {
+        assert!(Reader::new(&bytes[..len]).read_value().is_err(), "{len}");
+    }
+}
+
+#[test]
+fn invalid_header() {
+    let mut bytes = encode(&document(Value::Null));
+    let last = bytes.len() - 1;
+    bytes[last] = 0b00000111;
+    let err = read(&bytes);
+    assert!(
+        matches!(
+            err,
+            Error::InvalidHeader {
+                header: 0b00000111,
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+    assert_eq!(err.offset(), Some(last));
+    assert_eq!(err.path().unwrap().to_string(), "$.results[1].name");
+}
+
+#[test]
+fn invalid_utf8() {
+    let mut bytes = encode(&Value::Array(vec![Value::String("ab".into())]));
+    let last = bytes.len() - 1;
+    bytes[last] = 0xff;
+    let err = read(&bytes);
+    assert!(matches!(err, Error::InvalidUtf8 { .. }), "{err:?}");
+    assert_eq!(err.path().unwrap().to_string(), "$[0]");
+}
+
+#[test]
+fn depth_limit() {
+    let mut value = Value::Null;
+    for _ in 0..10 {
+        value = Value::Array(vec![value]);
+    }
+    let bytes = encode(&value);
+
+    let mut reader = Reader::new(&bytes);
+    reader.set_max_depth(9);
+    let err = reader.read_value().unwrap_err();
+    assert!(
+        matches!(err, Error::DepthLimit { offset: 20, .. }),
+        "{err:?}"
+    );
+    assert_eq!(
+        err.path().unwrap().to_string(),
+        "$[0][0][0][0][0][0][0][0][0]"
+    );
+
+    let mut reader = Reader::new(&bytes);
+    reader.set_max_depth(10);
+    assert_eq!(reader.read_value().unwrap(), value);
+}
+
+#[test]
+fn count_larger_than_input() {
+    // A typed array of float64 claiming 2^62 - 1 elements must fail before
+    // allocating or multiplying out of range.
+    let mut writer = Writer::new(0);
+    writer.append_uint8(0b01100100).unwrap();
+    writer.encode_compressed((1 << 62) - 1).unwrap();
+    writer.append_bytes(&[0; 16]).unwrap();
+    let err = read(writer.as_bytes());
+    assert!(
+        matches!(err, Error::UnexpectedEof { offset: 9, .. }),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn trailing_and_root_errors() {
+    let err = read(&[]);
+    assert!(
+        matches!(err, Error::UnexpectedEof { offset: 0, .. }),
+        "{err:?}"
+    );
+    assert!(err.path().unwrap().is_root());
+    assert_eq!(err.to_string(), "unexpected end of input at byte 0");
+}
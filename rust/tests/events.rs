@@ -0,0 +1,139 @@
+use std::io;
+
+use beve::{Error, Event, EventReader, Key, KeyRef, TypedArray, TypedArrayRef, Value, Writer};
+
+/// Hands out at most `step` bytes per read.
+struct ShortReads<'a> {
+    bytes: &'a [u8],
+    step: usize,
+}
+
+impl io::Read for ShortReads<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.step).min(self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(value).unwrap();
+    writer.into_bytes()
+}
+
+/// Reads `bytes` through a buffer of 64 bytes, 7 bytes at a time.
+fn events(bytes: &[u8]) -> EventReader<ShortReads<'_>> {
+    EventReader::with_capacity(64, ShortReads { bytes, step: 7 })
+}
+
+#[test]
+fn number_chunks_across_buffer_boundaries() {
+    let values: Vec<f64> = (0..1000).map(f64::from).collect();
+    let bytes = encode(&Value::TypedArray(TypedArray::F64(values.clone())));
+    let mut reader = events(&bytes);
+
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Event::TypedArray {
+            header: bytes[0],
+            len: 1000
+        })
+    );
+    let mut read = Vec::new();
+    let mut chunks = Vec::new();
+    while let Some(Event::Elements(TypedArrayRef::F64(numbers))) = reader.next_event().unwrap() {
+        chunks.push(numbers.len());
+        read.extend(numbers.iter());
+    }
+    assert_eq!(read, values);
+    assert_eq!(reader.next_event().unwrap(), None);
+
+    // Every chunk but the last takes at least half the buffer.
+    let (_, full) = chunks.split_last().unwrap();
+    assert!(full.iter().all(|&n| n >= 4), "{chunks:?}");
+}
+
+#[test]
+fn bit_chunks_across_buffer_boundaries() {
+    let flags: Vec<bool> = (0..2000).map(|i| i % 3 == 0).collect();
+    let bytes = encode(&Value::TypedArray(TypedArray::Bool(flags.clone())));
+    let mut reader = events(&bytes);
+
+    assert!(matches!(
+        reader.next_event().unwrap(),
+        Some(Event::TypedArray { len: 2000, .. })
+    ));
+    let mut read = Vec::new();
+    let mut chunks = Vec::new();
+    while let Some(Event::Elements(TypedArrayRef::Bool(bits))) = reader.next_event().unwrap() {
+        chunks.push(bits.len());
+        read.extend(bits.iter());
+    }
+    assert_eq!(read, flags);
+    let (_, full) = chunks.split_last().unwrap();
+    assert!(full.iter().all(|&n| n >= 32 * 8), "{chunks:?}");
+}
+
+#[test]
+fn characters_split_across_chunks() {
+    // The one byte character puts every other two byte character across the
+    // end of the buffer.
+    let text = format!("a{}", "é".repeat(200));
+    let bytes = encode(&Value::String(text.clone()));
+
+    for step in [1, 7, 64] {
+        let mut reader = EventReader::with_capacity(
+            64,
+            ShortReads {
+                bytes: &bytes,
+                step,
+            },
+        );
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Some(Event::String { len: text.len() })
+        );
+        let mut read = String::new();
+        let mut chunks = 0;
+        while let Some(Event::Str(chunk)) = reader.next_event().unwrap() {
+            read.push_str(chunk);
+            chunks += 1;
+        }
+        assert_eq!(read, text);
+        assert!(chunks > 1);
+        assert_eq!(reader.next_event().unwrap(), None);
+    }
+}
+
+#[test]
+fn key_larger_than_the_buffer() {
+    let key = "k".repeat(100);
+    let bytes = encode(&Value::Object(vec![(
+        Key::String(key.clone()),
+        Value::Null,
+    )]));
+
+    let mut reader = EventReader::with_capacity(64, &bytes[..]);
+    assert_eq!(reader.next_event().unwrap(), Some(Event::Object { len: 1 }));
+    let err = reader.next_event().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::TypeMismatch {
+                offset: 2,
+                expected: "a key that fits in the buffer",
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+
+    let mut reader = EventReader::with_capacity(128, &bytes[..]);
+    reader.next_event().unwrap();
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Event::Key(KeyRef::String(&key)))
+    );
+}
@@ -0,0 +1,67 @@
+use beve::F128;
+
+/// The quadruple precision float with the given sign, biased exponent and
+/// mantissa bits.
+fn f128(negative: bool, exponent: u128, mantissa: u128) -> F128 {
+    F128(u128::from(negative) << 127 | exponent << 112 | mantissa)
+}
+
+const BIAS: u128 = 16383;
+
+#[test]
+fn normals() {
+    assert_eq!(f128(false, BIAS, 0).to_f64(), 1.0);
+    assert_eq!(f128(true, BIAS, 0).to_f64(), -1.0);
+    assert_eq!(f128(false, BIAS, 1 << 111).to_f64(), 1.5);
+    assert_eq!(f128(false, BIAS + 1023, 0).to_f64(), 2f64.powi(1023));
+    assert_eq!(f128(false, BIAS - 1022, 0).to_f64(), f64::MIN_POSITIVE);
+    assert_eq!(f128(false, BIAS + 1023, (1 << 112) - 1).to_f64(), f64::MAX);
+}
+
+#[test]
+fn rounds_toward_zero() {
+    // Mantissa bits below the top 52 are dropped.
+    assert_eq!(f128(false, BIAS, 1).to_f64(), 1.0);
+    assert_eq!(f128(true, BIAS, (1 << 60) - 1).to_f64(), -1.0);
+    assert_eq!(
+        f128(false, BIAS, (1 << 112) - 1).to_f64(),
+        f64::from_bits(2f64.to_bits() - 1)
+    );
+}
+
+#[test]
+fn zeros_and_subnormals() {
+    assert_eq!(F128(0).to_f64().to_bits(), 0f64.to_bits());
+    assert_eq!(f128(true, 0, 0).to_f64().to_bits(), (-0f64).to_bits());
+
+    // Narrowing to f64 subnormals.
+    let smallest = f64::from_bits(1);
+    assert_eq!(f128(false, BIAS - 1074, 0).to_f64(), smallest);
+    assert_eq!(f128(true, BIAS - 1074, 0).to_f64(), -smallest);
+    assert_eq!(
+        f128(false, BIAS - 1023, 0).to_f64(),
+        f64::MIN_POSITIVE / 2.0
+    );
+    assert_eq!(f128(false, BIAS - 1075, 0).to_f64(), 0.0);
+
+    // Quadruple precision subnormals are far below the smallest f64.
+    let tiny = f128(true, 0, 1).to_f64();
+    assert_eq!(tiny.to_bits(), (-0f64).to_bits());
+    assert_eq!(f128(false, 0, (1 << 112) - 1).to_f64(), 0.0);
+}
+
+#[test]
+fn overflow_infinity_and_nan() {
+    assert_eq!(f128(false, BIAS + 1024, 0).to_f64(), f64::INFINITY);
+    assert_eq!(f128(true, 0x7ffe, 0).to_f64(), f64::NEG_INFINITY);
+    assert_eq!(f128(false, 0x7fff, 0).to_f64(), f64::INFINITY);
+    assert_eq!(f128(true, 0x7fff, 0).to_f64(), f64::NEG_INFINITY);
+
+    // A payload only in the low mantissa bits is still a NaN.
+    for mantissa in [1, 1 << 111, (1 << 112) - 1] {
+        let nan = f128(false, 0x7fff, mantissa).to_f64();
+        assert!(nan.is_nan() && nan.is_sign_positive());
+        let nan = f128(true, 0x7fff, mantissa).to_f64();
+        assert!(nan.is_nan() && nan.is_sign_negative());
+    }
+}
@@ -0,0 +1,89 @@
+use beve::{Reader, TypedArray, Value, Writer, BF16, F16};
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(value).unwrap();
+    writer.into_bytes()
+}
+
+#[test]
+fn f16_widening() {
+    assert_eq!(F16(0x3c00).to_f32(), 1.0);
+    assert_eq!(F16(0xc000).to_f32(), -2.0);
+    assert_eq!(F16(0x7bff).to_f32(), 65504.0);
+    assert_eq!(F16(0x3555).to_f32(), 0.333_251_95);
+    // Subnormals.
+    assert_eq!(F16(0x0001).to_f32(), 2f32.powi(-24));
+    assert_eq!(F16(0x8001).to_f32(), -(2f32.powi(-24)));
+    assert_eq!(F16(0x03ff).to_f32(), 1023.0 * 2f32.powi(-24));
+    // Zeros keep their sign.
+    assert_eq!(F16(0x0000).to_f32().to_bits(), 0);
+    assert_eq!(F16(0x8000).to_f32().to_bits(), 0x8000_0000);
+    assert_eq!(F16(0x7c00).to_f32(), f32::INFINITY);
+    assert_eq!(F16(0xfc00).to_f32(), f32::NEG_INFINITY);
+    assert!(F16(0x7e00).to_f32().is_nan());
+    assert!(F16(0xfc01).to_f32().is_nan());
+}
+
+#[test]
+fn bf16_widening() {
+    assert_eq!(BF16(0x3f80).to_f32(), 1.0);
+    assert_eq!(BF16(0xc040).to_f32(), -3.0);
+    assert_eq!(BF16(0x0001).to_f32(), f32::from_bits(0x0001_0000));
+    assert!(BF16(0x0001).to_f32().is_subnormal());
+    assert_eq!(BF16(0x8000).to_f32().to_bits(), 0x8000_0000);
+    assert_eq!(BF16(0x7f80).to_f32(), f32::INFINITY);
+    assert!(BF16(0x7fc0).to_f32().is_nan());
+    assert!(BF16(0xff81).to_f32().is_nan());
+}
+
+#[test]
+fn half_round_trip() {
+    let bf16 = Value::BF16(BF16(0x3f80));
+    let bytes = encode(&bf16);
+    assert_eq!(bytes, [0b00000001, 0x80, 0x3f]);
+    assert_eq!(Reader::new(&bytes).read_value().unwrap(), bf16);
+
+    let f16 = Value::F16(F16(0x3c00));
+    let bytes = encode(&f16);
+    assert_eq!(bytes, [0b00100001, 0x00, 0x3c]);
+    assert_eq!(Reader::new(&bytes).read_value().unwrap(), f16);
+
+    for array in [
+        TypedArray::BF16(vec![BF16(0x3f80), BF16(0x7fc0), BF16(0x0001)]),
+        TypedArray::F16(vec![F16(0x3c00), F16(0x7e00), F16(0x8001)]),
+    ] {
+        let value = Value::TypedArray(array);
+        assert_eq!(Reader::new(&encode(&value)).read_value().unwrap(), value);
+    }
+}
+
+#[test]
+fn widened_reading() {
+    let bytes = encode(&Value::Array(vec![
+        Value::BF16(BF16(0xc040)),
+        Value::F16(F16(0x0001)),
+        Value::TypedArray(TypedArray::F16(vec![F16(0x3c00), F16(0x7bff)])),
+    ]));
+    let mut reader = Reader::new(&bytes);
+    reader.set_widen_half(true);
+    assert_eq!(
+        reader.read_value().unwrap(),
+        Value::Array(vec![
+            Value::F32(-3.0),
+            Value::F32(2f32.powi(-24)),
+            Value::TypedArray(TypedArray::F32(vec![1.0, 65504.0])),
+        ])
+    );
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn widening_matches_half() {
+    for bits in 0..=u16::MAX {
+        let (ours, theirs) = (F16(bits).to_f32(), half::f16::from_bits(bits).to_f32());
+        assert!(ours.to_bits() == theirs.to_bits() || ours.is_nan() && theirs.is_nan());
+        let (ours, theirs) = (BF16(bits).to_f32(), half::bf16::from_bits(bits).to_f32());
+        assert!(ours.to_bits() == theirs.to_bits() || ours.is_nan() && theirs.is_nan());
+    }
+}
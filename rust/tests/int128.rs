@@ -0,0 +1,73 @@
+use beve::{Key, Reader, TypedArray, Value, Writer, F128};
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(value).unwrap();
+    writer.into_bytes()
+}
+
+fn round_trip(value: Value) {
+    assert_eq!(Reader::new(&encode(&value)).read_value().unwrap(), value);
+}
+
+#[test]
+fn scalars() {
+    let bytes = encode(&Value::I128(-2));
+    assert_eq!(bytes[0], 0b10001001);
+    assert_eq!(bytes[1..], (-2i128).to_le_bytes());
+    let bytes = encode(&Value::U128(u128::MAX));
+    assert_eq!(bytes[0], 0b10010001);
+    assert_eq!(bytes[1..], [0xff; 16]);
+
+    for v in [i128::MIN, -1, 0, i128::MAX] {
+        round_trip(Value::I128(v));
+    }
+    for v in [0, u64::MAX as u128 + 1, u128::MAX] {
+        round_trip(Value::U128(v));
+    }
+    round_trip(Value::F128(F128(0x3fff_0000_0000_0000_0000_0000_0000_0000)));
+}
+
+#[test]
+fn typed_arrays_and_keys() {
+    round_trip(Value::TypedArray(TypedArray::I128(vec![i128::MIN, 7])));
+    round_trip(Value::TypedArray(TypedArray::U128(vec![u128::MAX, 7])));
+    round_trip(Value::TypedArray(TypedArray::F128(vec![
+        F128(1),
+        F128(u128::MAX),
+    ])));
+    round_trip(Value::Object(vec![
+        (Key::I128(i128::MIN), Value::Null),
+        (Key::I128(i128::MAX), Value::Bool(true)),
+    ]));
+    round_trip(Value::Object(vec![(Key::U128(u128::MAX), Value::Null)]));
+}
+
+#[test]
+fn complex_parts() {
+    round_trip(Value::Complex(TypedArray::I128(vec![i128::MIN, i128::MAX])));
+    round_trip(Value::ComplexArray(TypedArray::U128(vec![
+        1,
+        2,
+        3,
+        u128::MAX,
+    ])));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let bytes = beve::to_vec(&(i128::MIN, u128::MAX)).unwrap();
+    assert_eq!(
+        beve::from_slice::<(i128, u128)>(&bytes).unwrap(),
+        (i128::MIN, u128::MAX)
+    );
+
+    let values = vec![i128::MIN, 0, i128::MAX];
+    let bytes = beve::to_vec(&values).unwrap();
+    assert_eq!(
+        Reader::new(&bytes).read_value().unwrap(),
+        Value::TypedArray(TypedArray::I128(values.clone()))
+    );
+    assert_eq!(beve::from_slice::<Vec<i128>>(&bytes).unwrap(), values);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "json")]
+
+use beve::{json, Key, Layout, Reader, TypedArray, Value};
+
+fn parse(text: &str) -> Value {
+    Reader::new(&json::from_json(text).unwrap())
+        .read_value()
+        .unwrap()
+}
+
+fn keys(value: &Value) -> Vec<&Key> {
+    value.as_object().unwrap().iter().map(|(k, _)| k).collect()
+}
+
+#[test]
+fn variant_and_matrix_forms() {
+    assert_eq!(
+        parse(r#"{"value": "x", "index": 2}"#),
+        Value::Variant {
+            index: 2,
+            value: Box::new(Value::String("x".into())),
+        }
+    );
+    assert_eq!(
+        parse(r#"{"value": [1, 2], "extents": [1, 2], "layout": "layout_left"}"#),
+        Value::Matrix {
+            layout: Layout::Left,
+            extents: vec![1, 2],
+            data: TypedArray::I64(vec![1, 2]),
+        }
+    );
+}
+
+#[test]
+fn lookalike_objects_keep_their_order() {
+    let value = parse(r#"{"value": 1, "index": "x"}"#);
+    assert_eq!(
+        keys(&value),
+        [&Key::String("value".into()), &Key::String("index".into())]
+    );
+
+    let value = parse(r#"{"value": [1, 2], "layout": "sideways", "extents": [2]}"#);
+    assert_eq!(
+        keys(&value),
+        [
+            &Key::String("value".into()),
+            &Key::String("layout".into()),
+            &Key::String("extents".into()),
+        ]
+    );
+}
+
+#[test]
+fn round_trip() {
+    let text = r#"{"b":[1,2,3],"a":{"index":1,"value":null},"c":"é"}"#;
+    let bytes = json::from_json(text).unwrap();
+    assert_eq!(json::to_json(&bytes).unwrap(), text);
+}
+
+#[test]
+fn number_arrays() {
+    assert_eq!(
+        parse("[1, -2]"),
+        Value::TypedArray(TypedArray::I64(vec![1, -2]))
+    );
+    assert_eq!(
+        parse("[1, 18446744073709551615]"),
+        Value::TypedArray(TypedArray::U64(vec![1, u64::MAX]))
+    );
+    assert_eq!(
+        parse("[1, -2, 0.5]"),
+        Value::TypedArray(TypedArray::F64(vec![1.0, -2.0, 0.5]))
+    );
+
+    // No typed array holds both, and f64 would round the large one.
+    let text = "[-1,9223372036854775808,18446744073709551615]";
+    assert_eq!(
+        parse(text),
+        Value::Array(vec![
+            Value::I64(-1),
+            Value::U64(1 << 63),
+            Value::U64(u64::MAX),
+        ])
+    );
+    let bytes = json::from_json(text).unwrap();
+    assert_eq!(json::to_json(&bytes).unwrap(), text);
+}
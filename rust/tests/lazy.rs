@@ -0,0 +1,185 @@
+use beve::{Error, Key, Lazy, TypedArray, Value, Writer};
+
+fn encode(values: &[Value]) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    for value in values {
+        writer.write_delimiter().unwrap();
+        writer.write_value(value).unwrap();
+    }
+    writer.into_bytes()
+}
+
+fn document() -> Value {
+    Value::Object(vec![
+        (Key::String("name".into()), Value::String("beve".into())),
+        (
+            Key::String("points".into()),
+            Value::Array(vec![Value::I32(1), Value::Null, Value::F64(2.5)]),
+        ),
+        (
+            Key::String("nested".into()),
+            Value::Object(vec![(Key::String("x".into()), Value::Bool(true))]),
+        ),
+    ])
+}
+
+#[test]
+fn get_and_index() {
+    let bytes = encode(&[document()]);
+    let root = Lazy::new(&bytes);
+    assert_eq!(root.position(), 1, "the leading delimiter is skipped");
+    assert_eq!(root.value().unwrap(), document());
+
+    let name = root.get("name").unwrap().unwrap();
+    assert_eq!(name.value().unwrap(), Value::String("beve".into()));
+    assert_eq!(name.value_ref().unwrap().as_str(), Some("beve"));
+
+    let points = root.get("points").unwrap().unwrap();
+    assert_eq!(
+        points.index(2).unwrap().unwrap().value().unwrap(),
+        Value::F64(2.5)
+    );
+    assert!(points.index(3).unwrap().is_none());
+
+    let x = root
+        .get("nested")
+        .unwrap()
+        .unwrap()
+        .get("x")
+        .unwrap()
+        .unwrap();
+    assert_eq!(x.value().unwrap(), Value::Bool(true));
+    assert_eq!(x.as_bytes().unwrap(), [0x18]);
+
+    assert!(root.get("missing").unwrap().is_none());
+}
+
+#[test]
+fn skip_between_delimited_values() {
+    let bytes = encode(&[Value::I8(1), Value::String("two".into()), Value::Null]);
+    let mut lazy = Lazy::new(&bytes);
+    let mut values = Vec::new();
+    while !lazy.is_at_end() {
+        values.push(lazy.value().unwrap());
+        lazy.skip_value().unwrap();
+    }
+    assert_eq!(
+        values,
+        [Value::I8(1), Value::String("two".into()), Value::Null]
+    );
+}
+
+#[test]
+fn get_on_integer_keyed_objects() {
+    let objects = [
+        Value::Object(vec![(Key::U8(1), Value::Null), (Key::U8(3), Value::I8(4))]),
+        Value::Object(vec![
+            (Key::I16(-1), Value::Null),
+            (Key::I16(3), Value::I8(4)),
+        ]),
+        Value::Object(vec![
+            (Key::U128(u128::MAX), Value::Null),
+            (Key::U128(3), Value::I8(4)),
+        ]),
+        Value::Object(vec![
+            (Key::I128(i128::MIN), Value::Null),
+            (Key::I128(3), Value::I8(4)),
+        ]),
+    ];
+    for object in objects {
+        let bytes = encode(&[object]);
+        let lazy = Lazy::new(&bytes);
+        let found = lazy.get("3").unwrap().unwrap();
+        assert_eq!(found.value().unwrap(), Value::I8(4));
+        for missing in ["2", "-3", "x", "", "1e3"] {
+            assert!(lazy.get(missing).unwrap().is_none(), "{missing}");
+        }
+    }
+
+    let bytes = encode(&[Value::Object(vec![(Key::I64(-7), Value::Bool(true))])]);
+    let found = Lazy::new(&bytes).get("-7").unwrap().unwrap();
+    assert_eq!(found.value().unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn lookups_in_the_wrong_container() {
+    let bytes = encode(&[document()]);
+    let root = Lazy::new(&bytes);
+    let err = root.index(0).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::TypeMismatch {
+                offset: 1,
+                expected: "a generic array",
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+
+    let points = root.get("points").unwrap().unwrap();
+    let err = points.get("x").unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::TypeMismatch {
+                expected: "an object",
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+    let name = root.get("name").unwrap().unwrap();
+    assert!(name.get("x").is_err());
+    assert!(name.index(0).is_err());
+
+    // Typed array elements are read from the decoded array.
+    let bytes = encode(&[Value::TypedArray(TypedArray::F64(vec![0.5, 1.5]))]);
+    let lazy = Lazy::new(&bytes);
+    let err = lazy.index(1).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::TypeMismatch {
+                offset: 1,
+                expected: "a generic array",
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+    let Value::TypedArray(array) = lazy.value().unwrap() else {
+        panic!("not a typed array");
+    };
+    assert_eq!(array.get(1), Some(Value::F64(1.5)));
+}
+
+#[test]
+fn delimiter_in_place_of_a_member() {
+    // {"a": <delimiter>, "b": null}
+    let object = [0x03, 2 << 2, 1 << 2, b'a', 0x06, 1 << 2, b'b', 0x00];
+    let lazy = Lazy::new(&object);
+    let err = lazy.get("b").unwrap_err();
+    assert!(
+        matches!(err, Error::TypeMismatch { offset: 4, .. }),
+        "{err:?}"
+    );
+    let a = lazy.get("a").unwrap().unwrap();
+    assert!(matches!(
+        a.value(),
+        Err(Error::TypeMismatch { offset: 4, .. })
+    ));
+    assert!(a.value_ref().is_err());
+    assert!(a.as_bytes().is_err());
+
+    // [<delimiter>, null]
+    let array = [0x05, 2 << 2, 0x06, 0x00];
+    let lazy = Lazy::new(&array);
+    assert!(matches!(
+        lazy.index(1),
+        Err(Error::TypeMismatch { offset: 2, .. })
+    ));
+    assert!(lazy.index(0).unwrap().unwrap().value().is_err());
+    assert!(lazy.value().is_err());
+}
@@ -0,0 +1,93 @@
+use beve::{Error, Layout, Matrix, Reader, TypedArray, Value, Writer};
+
+fn matrix(extents: Vec<u64>, len: usize) -> Matrix<f64> {
+    Matrix {
+        layout: Layout::Right,
+        extents,
+        data: (0..len).map(|i| i as f64).collect(),
+    }
+}
+
+fn assert_mismatch(err: Error, at: usize) {
+    assert!(
+        matches!(
+            err,
+            Error::TypeMismatch {
+                offset,
+                expected: "as many elements as the extents describe",
+                ..
+            } if offset == at
+        ),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn matrix_round_trip() {
+    for (extents, len) in [(vec![2, 3], 6), (vec![], 1), (vec![4, 0, 2], 0)] {
+        let matrix = matrix(extents, len);
+        let mut writer = Writer::new(0);
+        writer.write_matrix(&matrix).unwrap();
+        let value = Reader::new(writer.as_bytes()).read_value().unwrap();
+        assert_eq!(value, Value::from(matrix.clone()));
+        assert_eq!(Matrix::try_from(value), Ok(matrix));
+    }
+}
+
+#[test]
+fn writing_data_that_does_not_fit_the_extents() {
+    let cases = [
+        (vec![2, 3], 5),
+        (vec![2, 3], 7),
+        (vec![], 0),
+        (vec![u64::MAX, 2], 0),
+        (vec![1 << 32, 1 << 32], 0),
+    ];
+    for (extents, len) in cases {
+        let matrix = matrix(extents, len);
+
+        let mut writer = Writer::new(0);
+        writer.write_value(&Value::Null).unwrap();
+        assert_mismatch(writer.write_matrix(&matrix).unwrap_err(), 1);
+        assert_eq!(writer.as_bytes().len(), 1);
+
+        let value = Value::from(matrix);
+        assert_mismatch(writer.write_value(&value).unwrap_err(), 1);
+        assert_eq!(writer.as_bytes().len(), 1);
+    }
+}
+
+#[test]
+fn reading_data_that_does_not_fit_the_extents() {
+    // Written as a matrix of one row, then the extents patched in place.
+    let mut writer = Writer::new(0);
+    writer.write_matrix(&matrix(vec![5], 5)).unwrap();
+    let mut bytes = writer.into_bytes();
+    let extent = 4;
+    let data = extent + 8;
+    assert_eq!(bytes[extent..data], 5u64.to_le_bytes());
+
+    for wrong in [4, 6, 0] {
+        bytes[extent..data].copy_from_slice(&u64::to_le_bytes(wrong));
+        assert_mismatch(Reader::new(&bytes).read_value().unwrap_err(), data);
+        assert_mismatch(Reader::new(&bytes).read_value_ref().unwrap_err(), data);
+    }
+
+    let mut writer = Writer::new(0);
+    writer.write_matrix(&matrix(vec![1, 1], 1)).unwrap();
+    let mut bytes = writer.into_bytes();
+    bytes[4..20].copy_from_slice(&[0xff; 16]);
+    assert_mismatch(Reader::new(&bytes).read_value().unwrap_err(), 20);
+
+    // The data of another type is checked the same way.
+    let value = Value::Matrix {
+        layout: Layout::Left,
+        extents: vec![3],
+        data: TypedArray::U8(vec![1, 2, 3]),
+    };
+    let mut writer = Writer::new(0);
+    writer.write_value(&value).unwrap();
+    let mut bytes = writer.into_bytes();
+    bytes[4] = 2;
+    assert_mismatch(Reader::new(&bytes).read_value().unwrap_err(), 12);
+}
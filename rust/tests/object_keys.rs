@@ -0,0 +1,30 @@
+use beve::{Error, Key, Reader, Value, Writer};
+
+#[test]
+fn integer_keys_round_trip() {
+    let value = Value::Object(vec![
+        (Key::I32(-1), Value::Bool(true)),
+        (Key::I32(7), Value::Null),
+    ]);
+    let mut writer = Writer::new(0);
+    writer.write_value(&value).unwrap();
+    assert_eq!(Reader::new(writer.as_bytes()).read_value().unwrap(), value);
+}
+
+#[test]
+fn mixed_keys_write_nothing() {
+    let value = Value::Object(vec![
+        (Key::String("a".into()), Value::Null),
+        (Key::U8(1), Value::Null),
+    ]);
+    let mut writer = Writer::new(0);
+    writer.write_value(&Value::U8(5)).unwrap();
+    let before = writer.as_bytes().to_vec();
+
+    let err = writer.write_value(&value).unwrap_err();
+    assert!(
+        matches!(err, Error::TypeMismatch { offset: 2, .. }),
+        "{err:?}"
+    );
+    assert_eq!(writer.as_bytes(), before);
+}
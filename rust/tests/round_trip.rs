@@ -0,0 +1,35 @@
+use beve::{Reader, Value, Writer};
+
+fn round_trip(value: Value) {
+    let mut writer = Writer::new(0);
+    writer.write_value(&value).unwrap();
+    assert_eq!(Reader::new(writer.as_bytes()).read_value().unwrap(), value);
+}
+
+#[test]
+fn scalars() {
+    round_trip(Value::Null);
+    round_trip(Value::Bool(true));
+    round_trip(Value::Bool(false));
+    round_trip(Value::I8(-1));
+    round_trip(Value::I16(-300));
+    round_trip(Value::I32(i32::MIN));
+    round_trip(Value::I64(i64::MAX));
+    round_trip(Value::U8(u8::MAX));
+    round_trip(Value::U16(300));
+    round_trip(Value::U32(u32::MAX));
+    round_trip(Value::U64(u64::MAX));
+    round_trip(Value::F32(0.5));
+    round_trip(Value::F64(-1.25));
+}
+
+#[test]
+fn reader_and_writer() {
+    let mut writer = Writer::new(0);
+    writer.write_value(&Value::I32(5)).unwrap();
+    assert_eq!(writer.as_bytes(), [0b01001001, 5, 0, 0, 0]);
+
+    let mut reader = Reader::new(writer.as_bytes());
+    assert_eq!(reader.read_value().unwrap(), Value::I32(5));
+    assert_eq!(reader.position(), 5);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use beve::{Error, Key, Reader, Value};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// `len` entries of a map with a size hint of `hint`.
+struct Hinted {
+    hint: usize,
+    len: usize,
+}
+
+impl Serialize for Hinted {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.hint))?;
+        for i in 0..self.len {
+            map.serialize_entry(&(i as u32), &true)?;
+        }
+        map.end()
+    }
+}
+
+#[test]
+fn maps_of_known_length() {
+    let map: BTreeMap<u32, bool> = [(0, true), (1, true)].into_iter().collect();
+    let bytes = beve::to_vec(&map).unwrap();
+    assert_eq!(
+        Reader::new(&bytes).read_value().unwrap(),
+        Value::Object(vec![
+            (Key::U32(0), Value::Bool(true)),
+            (Key::U32(1), Value::Bool(true)),
+        ])
+    );
+
+    let exact = Hinted { hint: 2, len: 2 };
+    assert_eq!(beve::to_vec(&exact).unwrap(), bytes);
+}
+
+#[test]
+fn maps_longer_or_shorter_than_their_size_hint() {
+    for (hint, len) in [(3, 2), (1, 2), (1, 0)] {
+        let err = beve::to_vec(&Hinted { hint, len }).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                Error::Message {
+                    offset: Some(0),
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert!(
+            err.to_string()
+                .contains("map length differs from its size hint"),
+            "{err}"
+        );
+    }
+}
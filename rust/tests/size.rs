@@ -0,0 +1,88 @@
+use beve::{Reader, Value, Writer};
+
+fn encode(n: u64) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.encode_compressed(n).unwrap();
+    writer.into_bytes()
+}
+
+#[test]
+fn compressed_boundaries() {
+    assert_eq!(encode(0), [0]);
+    assert_eq!(encode(63), [63 << 2]);
+    assert_eq!(encode(64), [1 | (64 << 2) as u8, 1]);
+    assert_eq!(
+        encode((1 << 14) - 1),
+        ((((1u16 << 14) - 1) << 2) | 1).to_le_bytes()
+    );
+    assert_eq!(encode(1 << 14), ((1u32 << 16) | 2).to_le_bytes());
+    assert_eq!(
+        encode((1 << 30) - 1),
+        ((((1u32 << 30) - 1) << 2) | 2).to_le_bytes()
+    );
+    assert_eq!(encode(1 << 30), ((1u64 << 32) | 3).to_le_bytes());
+    assert_eq!(encode((1 << 62) - 1), u64::MAX.to_le_bytes());
+}
+
+#[test]
+fn compressed_too_large() {
+    let mut writer = Writer::new(0);
+    assert!(writer.encode_compressed(1 << 62).is_err());
+    assert!(writer.encode_compressed(u64::MAX).is_err());
+    assert!(writer.as_bytes().is_empty());
+}
+
+#[test]
+fn compressed_round_trip() {
+    for n in [
+        0,
+        63,
+        64,
+        (1 << 14) - 1,
+        1 << 14,
+        (1 << 30) - 1,
+        1 << 30,
+        u32::MAX.into(),
+        (1 << 62) - 1,
+    ] {
+        let bytes = encode(n);
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.read_compressed().unwrap(), n);
+        assert_eq!(reader.position(), bytes.len());
+    }
+}
+
+#[test]
+fn truncated_compressed() {
+    assert!(Reader::new(&[]).read_compressed().is_err());
+    for n in [64, 1 << 14, 1 << 30] {
+        let bytes = encode(n);
+        for len in 1..bytes.len() {
+            let mut reader = Reader::new(&bytes[..len]);
+            assert!(reader.read_compressed().is_err(), "{n} cut to {len}");
+        }
+    }
+}
+
+#[test]
+fn truncated_sizes_of_values() {
+    // A string, an object and a generic array whose SIZE takes two bytes but
+    // only has one.
+    for header in [2, 3, 5] {
+        assert!(Reader::new(&[header, 1]).read_value().is_err());
+    }
+}
+
+#[test]
+fn sizes_of_values() {
+    for len in [63, 64, 1 << 14] {
+        let value = Value::String("x".repeat(len));
+        let mut writer = Writer::new(0);
+        writer.write_value(&value).unwrap();
+        assert_eq!(
+            writer.as_bytes()[1..][..encode(len as u64).len()],
+            encode(len as u64)
+        );
+        assert_eq!(Reader::new(writer.as_bytes()).read_value().unwrap(), value);
+    }
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "serde")]
+
+use std::io;
+
+use beve::{Error, StreamDeserializer};
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    id: u32,
+    name: String,
+    values: Vec<f64>,
+}
+
+/// A record whose `Deserialize` impl replaces every error with its own, as
+/// impls that add context do.
+#[derive(Debug, PartialEq)]
+struct Wrapped(Record);
+
+impl<'de> Deserialize<'de> for Wrapped {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Record::deserialize(deserializer)
+            .map(Wrapped)
+            .map_err(|err| serde::de::Error::custom(format!("bad record: {err}")))
+    }
+}
+
+/// Hands out at most `step` bytes per read.
+struct ShortReads<'a> {
+    bytes: &'a [u8],
+    step: usize,
+}
+
+impl io::Read for ShortReads<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.step).min(self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+fn records() -> Vec<Record> {
+    (0..20)
+        .map(|id| Record {
+            id,
+            name: "x".repeat(id as usize * 100),
+            values: (0..id * 50).map(f64::from).collect(),
+        })
+        .collect()
+}
+
+/// The records separated by one or two delimiters, or none, with delimiters
+/// in front and at the end.
+fn encode(records: &[Record]) -> Vec<u8> {
+    let mut bytes = vec![0x06];
+    for (i, record) in records.iter().enumerate() {
+        bytes.extend(beve::to_vec(record).unwrap());
+        bytes.extend(std::iter::repeat_n(0x06, i % 3));
+    }
+    bytes
+}
+
+#[test]
+fn slice_stream() {
+    let records = records();
+    let bytes = encode(&records);
+    let read: Vec<Record> = StreamDeserializer::from_slice(&bytes)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read, records);
+}
+
+#[test]
+fn reader_stream_with_short_reads() {
+    let records = records();
+    let bytes = encode(&records);
+    for step in [1, 7, 4096, usize::MAX] {
+        let reader = ShortReads {
+            bytes: &bytes,
+            step,
+        };
+        let mut stream = StreamDeserializer::from_reader(reader);
+        let read: Vec<Record> = stream.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, records);
+        assert_eq!(stream.byte_offset(), bytes.len());
+    }
+}
+
+#[test]
+fn truncated_final_value() {
+    let records = records();
+    let mut bytes = encode(&records[..3]);
+    let last = beve::to_vec(&records[3]).unwrap();
+    let start = bytes.len();
+    bytes.extend(&last[..last.len() - 1]);
+
+    let mut stream = StreamDeserializer::<_, Record>::from_reader(ShortReads {
+        bytes: &bytes,
+        step: 5,
+    });
+    for record in &records[..3] {
+        assert_eq!(&stream.next().unwrap().unwrap(), record);
+    }
+    match stream.next() {
+        Some(Err(Error::UnexpectedEof { offset, .. })) => {
+            assert!(offset > start && offset <= bytes.len(), "{offset}")
+        }
+        other => panic!("{other:?}"),
+    }
+    assert!(stream.next().is_none());
+
+    let mut stream = StreamDeserializer::<_, Record>::from_slice(&bytes);
+    assert_eq!(stream.by_ref().take(3).filter(Result::is_ok).count(), 3);
+    assert!(matches!(
+        stream.next(),
+        Some(Err(Error::UnexpectedEof { .. }))
+    ));
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn records_spanning_a_refill_with_wrapped_errors() {
+    let records = records();
+    let bytes = encode(&records);
+    for step in [1, 7, usize::MAX] {
+        let reader = ShortReads {
+            bytes: &bytes,
+            step,
+        };
+        let read: Vec<Wrapped> = StreamDeserializer::from_reader(reader)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read.len(), records.len());
+        assert!(read
+            .iter()
+            .zip(&records)
+            .all(|(read, record)| read.0 == *record));
+    }
+
+    // A record cut short still fails, with the wrapped message.
+    let mut stream = StreamDeserializer::<_, Wrapped>::from_reader(ShortReads {
+        bytes: &bytes[..bytes.len() - 2],
+        step: 7,
+    });
+    let err = stream.by_ref().find_map(Result::err).unwrap();
+    match err {
+        Error::Message { msg, .. } => assert!(msg.starts_with("bad record"), "{msg}"),
+        other => panic!("{other:?}"),
+    }
+    assert!(stream.next().is_none());
+}
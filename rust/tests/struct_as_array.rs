@@ -0,0 +1,189 @@
+#![cfg(all(feature = "derive", feature = "serde"))]
+
+use beve::{Beve, BeveRead, BeveWrite, Reader, Value, Writer};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Beve)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Beve)]
+struct Segment {
+    name: String,
+    from: Point,
+    to: Point,
+    weights: Vec<f64>,
+    points: Vec<Point>,
+    note: Option<String>,
+}
+
+fn segment() -> Segment {
+    Segment {
+        name: "s".into(),
+        from: Point { x: 1, y: 2 },
+        to: Point { x: -3, y: 4 },
+        weights: vec![0.5, 1.5],
+        points: vec![Point::default(), Point { x: 5, y: 6 }],
+        note: None,
+    }
+}
+
+#[derive(Debug, PartialEq, Beve)]
+struct Labeled {
+    label: String,
+    #[beve(flatten)]
+    point: Point,
+    #[beve(skip)]
+    cache: u8,
+    #[beve(default)]
+    count: u32,
+}
+
+fn derived<T: BeveWrite>(value: &T) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.set_struct_as_array(true);
+    writer.write(value).unwrap();
+    writer.into_bytes()
+}
+
+fn serialized<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.set_struct_as_array(true);
+    value
+        .serialize(&mut beve::Serializer::new(&mut writer))
+        .unwrap();
+    writer.into_bytes()
+}
+
+fn array(values: Vec<Value>) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(&Value::Array(values)).unwrap();
+    writer.into_bytes()
+}
+
+fn read_both<T>(bytes: &[u8]) -> (Result<T, beve::Error>, Result<T, beve::Error>)
+where
+    T: BeveRead + for<'de> Deserialize<'de>,
+{
+    (Reader::new(bytes).read(), beve::from_slice(bytes))
+}
+
+#[test]
+fn nested_structs_as_arrays() {
+    let bytes = derived(&segment());
+    assert_eq!(bytes, serialized(&segment()));
+
+    let point = |x, y| Value::Array(vec![Value::I32(x), Value::I32(y)]);
+    let Value::Array(values) = Reader::new(&bytes).read_value().unwrap() else {
+        panic!("not an array");
+    };
+    assert_eq!(values.len(), 6);
+    assert_eq!(values[1], point(1, 2));
+    assert_eq!(values[2], point(-3, 4));
+    assert_eq!(values[4], Value::Array(vec![point(0, 0), point(5, 6)]));
+
+    let (derive, serde) = read_both::<Segment>(&bytes);
+    assert_eq!(derive.unwrap(), segment());
+    assert_eq!(serde.unwrap(), segment());
+}
+
+#[test]
+fn flattened_fields_take_their_own_positions() {
+    let labeled = Labeled {
+        label: "l".into(),
+        point: Point { x: 7, y: 8 },
+        cache: 9,
+        count: 2,
+    };
+    let bytes = derived(&labeled);
+    assert_eq!(
+        bytes,
+        array(vec![
+            Value::String("l".into()),
+            Value::I32(7),
+            Value::I32(8),
+            Value::U32(2),
+        ])
+    );
+    assert_eq!(
+        Reader::new(&bytes).read::<Labeled>().unwrap(),
+        Labeled {
+            cache: 0,
+            ..labeled
+        }
+    );
+
+    // A defaulted field may be left off the end.
+    let short = array(vec![
+        Value::String("l".into()),
+        Value::I32(7),
+        Value::I32(8),
+    ]);
+    let read: Labeled = Reader::new(&short).read().unwrap();
+    assert_eq!((read.point, read.count), (Point { x: 7, y: 8 }, 0));
+}
+
+#[test]
+fn missing_values_are_an_error() {
+    let bytes = array(vec![Value::I32(1)]);
+    let (derive, serde) = read_both::<Point>(&bytes);
+    let derive = derive.unwrap_err().to_string();
+    assert!(derive.contains("missing field `y`"), "{derive}");
+    let serde = serde.unwrap_err().to_string();
+    assert!(serde.contains("invalid length 1"), "{serde}");
+}
+
+#[test]
+fn extra_values_are_skipped() {
+    let extra = || {
+        vec![
+            Value::I32(1),
+            Value::I32(2),
+            Value::String("extra".into()),
+            Value::Array(vec![Value::Null, Value::Bool(true)]),
+        ]
+    };
+    let (derive, serde) = read_both::<Point>(&array(extra()));
+    assert_eq!(derive.unwrap(), Point { x: 1, y: 2 });
+    assert_eq!(serde.unwrap(), Point { x: 1, y: 2 });
+
+    // Nested, the skipped values must not shift the fields that follow.
+    let bytes = array(vec![
+        Value::String("s".into()),
+        Value::Array(extra()),
+        Value::Array(vec![Value::I32(-3), Value::I32(4), Value::I32(0)]),
+        Value::Array(vec![Value::F64(0.5), Value::F64(1.5)]),
+        Value::Array(vec![
+            Value::Array(vec![Value::I32(0); 3]),
+            Value::Array(extra()),
+        ]),
+        Value::Null,
+    ]);
+    let expected = Segment {
+        points: vec![Point::default(), Point { x: 1, y: 2 }],
+        to: Point { x: -3, y: 4 },
+        from: Point { x: 1, y: 2 },
+        ..segment()
+    };
+    let (derive, serde) = read_both::<Segment>(&bytes);
+    assert_eq!(derive.unwrap(), expected);
+    assert_eq!(serde.unwrap(), expected);
+}
+
+#[test]
+fn extra_typed_array_values_are_skipped() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Pair(u16, u16);
+
+    let mut writer = Writer::new(0);
+    writer
+        .write_value(&Value::Array(vec![
+            Value::TypedArray(beve::TypedArray::U16(vec![1, 2, 3])),
+            Value::U8(4),
+        ]))
+        .unwrap();
+    let read: (Pair, u8) = beve::from_slice(writer.as_bytes()).unwrap();
+    assert_eq!(read, (Pair(1, 2), 4));
+}
@@ -0,0 +1,206 @@
+use beve::{
+    Error, Layout, Matrix, Reader, TypedArray, TypedArrayRef, Value, Writer, BF16, F128, F16,
+};
+use num_complex::Complex;
+
+/// A typed array with the given HEADER and elements, each already encoded,
+/// after `offset` bytes of padding so that its DATA can start unaligned.
+fn typed_array(offset: usize, header: u8, elements: &[&[u8]]) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.append_bytes(&vec![0; offset]).unwrap();
+    writer.append_uint8(header).unwrap();
+    writer.encode_compressed(elements.len() as u64).unwrap();
+    for element in elements {
+        writer.append_bytes(element).unwrap();
+    }
+    writer.into_bytes()
+}
+
+/// Reads a typed array one element at a time with `read`.
+fn one_by_one<T>(
+    bytes: &[u8],
+    offset: usize,
+    read: impl Fn(&mut Reader) -> Result<T, Error>,
+) -> Vec<T> {
+    let mut reader = Reader::new(&bytes[offset..]);
+    reader.read_uint8().unwrap();
+    let n = reader.read_compressed().unwrap();
+    (0..n).map(|_| read(&mut reader).unwrap()).collect()
+}
+
+fn bulk(bytes: &[u8], offset: usize) -> TypedArray {
+    match Reader::new(&bytes[offset..]).read_value().unwrap() {
+        Value::TypedArray(array) => array,
+        value => panic!("not a typed array: {value:?}"),
+    }
+}
+
+#[test]
+fn i64_arrays() {
+    let values = [i64::MIN, -1, 0, 1, i64::MAX];
+    let elements: Vec<[u8; 8]> = values.iter().map(|v| v.to_le_bytes()).collect();
+    let elements: Vec<&[u8]> = elements.iter().map(|e| &e[..]).collect();
+    for offset in 0..8 {
+        let bytes = typed_array(offset, 0b01101100, &elements);
+        let expected = one_by_one(&bytes, offset, |r| r.read_int64());
+        assert_eq!(expected, values);
+        assert_eq!(bulk(&bytes, offset), TypedArray::I64(expected));
+    }
+}
+
+#[test]
+fn u64_arrays() {
+    let values = [0, 1, u64::from(u32::MAX) + 1, u64::MAX];
+    let elements: Vec<[u8; 8]> = values.iter().map(|v| v.to_le_bytes()).collect();
+    let elements: Vec<&[u8]> = elements.iter().map(|e| &e[..]).collect();
+    for offset in 0..8 {
+        let bytes = typed_array(offset, 0b01110100, &elements);
+        let expected = one_by_one(&bytes, offset, |r| r.read_uint64());
+        assert_eq!(expected, values);
+        assert_eq!(bulk(&bytes, offset), TypedArray::U64(expected));
+    }
+}
+
+#[test]
+fn f64_arrays() {
+    let values = [f64::MIN, -0.0, 0.5, f64::INFINITY, f64::MAX];
+    let elements: Vec<[u8; 8]> = values.iter().map(|v| v.to_le_bytes()).collect();
+    let elements: Vec<&[u8]> = elements.iter().map(|e| &e[..]).collect();
+    for offset in 0..8 {
+        let bytes = typed_array(offset, 0b01100100, &elements);
+        let expected = one_by_one(&bytes, offset, |r| r.read_double());
+        assert_eq!(bulk(&bytes, offset), TypedArray::F64(expected));
+    }
+}
+
+#[test]
+fn truncated_arrays() {
+    let bytes = typed_array(0, 0b01101100, &[&[0; 8], &[0; 8]]);
+    for len in 2..bytes.len() {
+        let err = Reader::new(&bytes[..len]).read_value().unwrap_err();
+        assert!(
+            matches!(err, Error::UnexpectedEof { offset: 2, .. }),
+            "{err:?}"
+        );
+    }
+}
+
+#[test]
+fn written_arrays_round_trip() {
+    for array in [
+        TypedArray::I64(vec![i64::MIN, -5, i64::MAX]),
+        TypedArray::U64(vec![u64::MAX, 0]),
+        TypedArray::F64(vec![]),
+    ] {
+        let value = Value::TypedArray(array);
+        let mut writer = Writer::new(0);
+        writer.write_value(&value).unwrap();
+        assert_eq!(Reader::new(writer.as_bytes()).read_value().unwrap(), value);
+    }
+}
+
+/// The HEADER of a typed array of the given number type and byte count
+/// index.
+fn header(num_type: u8, byte_count_index: u8) -> u8 {
+    0b100 | num_type << 3 | byte_count_index << 5
+}
+
+/// The encoding of a numeric typed array, built one element at a time.
+macro_rules! expected {
+    ($header:expr, $values:expr, $to_bytes:expr) => {{
+        let mut bytes = vec![$header, ($values.len() as u8) << 2];
+        for &v in &$values {
+            bytes.extend_from_slice(&$to_bytes(v));
+        }
+        bytes
+    }};
+}
+
+#[test]
+fn every_width_in_bulk() {
+    macro_rules! check {
+        ($variant:ident: $t:ty, $header:expr, $values:expr) => {
+            check!($variant, $header, $values, <$t>::to_le_bytes)
+        };
+        ($variant:ident, $header:expr, $values:expr, $to_bytes:expr) => {{
+            let values = $values;
+            let expected = expected!($header, values, $to_bytes);
+            let value = Value::TypedArray(TypedArray::$variant(values.to_vec()));
+            let mut writer = Writer::new(0);
+            writer.write_value(&value).unwrap();
+            assert_eq!(writer.as_bytes(), expected, "{}", stringify!($variant));
+
+            // Read at every alignment, owned and borrowed.
+            for offset in 0..16 {
+                let mut bytes = vec![0; offset];
+                bytes.extend_from_slice(&expected);
+                let bytes = &bytes[offset..];
+                assert_eq!(Reader::new(bytes).read_value().unwrap(), value);
+                let read = Reader::new(bytes).read_value_ref().unwrap();
+                let Some(TypedArrayRef::$variant(numbers)) = read.as_typed_array() else {
+                    panic!("not a typed array: {read:?}");
+                };
+                assert_eq!(numbers.to_vec(), values);
+                assert_eq!(numbers.iter().collect::<Vec<_>>(), values);
+            }
+        }};
+    }
+
+    check!(I8: i8, header(1, 0), [i8::MIN, -1, 0, i8::MAX]);
+    check!(I16: i16, header(1, 1), [i16::MIN, -1, 0x1234, i16::MAX]);
+    check!(I32: i32, header(1, 2), [i32::MIN, -1, 0x1234_5678, i32::MAX]);
+    check!(
+        I64: i64,
+        header(1, 3),
+        [i64::MIN, -1, 0x1234_5678_9abc, i64::MAX]
+    );
+    check!(I128: i128, header(1, 4), [i128::MIN, -1, i128::MAX]);
+    check!(U8: u8, header(2, 0), [0, 1, u8::MAX]);
+    check!(U16: u16, header(2, 1), [0, 0x1234, u16::MAX]);
+    check!(U32: u32, header(2, 2), [0, 0x1234_5678, u32::MAX]);
+    check!(U64: u64, header(2, 3), [0, 0x1234_5678_9abc, u64::MAX]);
+    check!(U128: u128, header(2, 4), [0, u128::MAX >> 1, u128::MAX]);
+    check!(F32: f32, header(0, 2), [f32::MIN, -0.5, 0.0, f32::INFINITY]);
+    check!(F64: f64, header(0, 3), [f64::MIN, -0.5, 0.0, f64::INFINITY]);
+    // The raw bits of the half and quadruple precision floats.
+    let bf16 = |v: BF16| v.0.to_le_bytes();
+    let f16 = |v: F16| v.0.to_le_bytes();
+    let f128 = |v: F128| v.0.to_le_bytes();
+    check!(BF16, header(0, 0), [BF16(0x3f80), BF16(0xff80)], bf16);
+    check!(F16, header(0, 1), [F16(0x3c00), F16(0x8001)], f16);
+    check!(F128, header(0, 4), [F128(1), F128(u128::MAX)], f128);
+}
+
+#[test]
+fn complex_arrays_in_bulk() {
+    let values = [Complex::new(1.5f64, -2.0), Complex::new(0.0, f64::MAX)];
+    let mut writer = Writer::new(0);
+    writer.write_complex_array(&values).unwrap();
+
+    let parts = [1.5, -2.0, 0.0, f64::MAX];
+    let mut expected = vec![0b00011110, header(0, 3) & !0b111 | 1, 2 << 2];
+    for v in parts {
+        expected.extend_from_slice(&v.to_le_bytes());
+    }
+    assert_eq!(writer.as_bytes(), expected);
+    assert_eq!(
+        Reader::new(&expected).read_value().unwrap(),
+        Value::from(values.to_vec())
+    );
+}
+
+#[test]
+fn matrices_in_bulk() {
+    let matrix = Matrix {
+        layout: Layout::Left,
+        extents: vec![2, 3],
+        data: vec![1i32, -2, 3, -4, 5, -6],
+    };
+    let mut writer = Writer::new(0);
+    writer.write_matrix(&matrix).unwrap();
+
+    let mut expected = vec![0b00010110, 1];
+    expected.extend(expected!(header(2, 3), matrix.extents, u64::to_le_bytes));
+    expected.extend(expected!(header(1, 2), matrix.data, i32::to_le_bytes));
+    assert_eq!(writer.as_bytes(), expected);
+}
@@ -0,0 +1,73 @@
+use beve::{Key, TypedArray, Value};
+
+fn document() -> Value {
+    Value::Object(vec![
+        ("name".into(), "doc".into()),
+        (
+            "items".into(),
+            Value::Array(vec![
+                Value::I32(1),
+                Value::Object(vec![("x".into(), 2.5f64.into())]),
+            ]),
+        ),
+        ("values".into(), vec![1u16, 2, 3].into()),
+        (Key::I32(7), Value::Null),
+    ])
+}
+
+#[test]
+fn indexing() {
+    let doc = document();
+    assert_eq!(doc["name"].as_str(), Some("doc"));
+    assert_eq!(doc["items"][0].as_i64(), Some(1));
+    assert_eq!(doc["items"][1]["x"].as_f64(), Some(2.5));
+    assert!(doc["missing"].is_null());
+    assert!(doc["items"][9].is_null());
+    assert!(doc["name"][0].is_null());
+    assert_eq!(
+        doc.get("items").and_then(|v| v.get_index(1)),
+        Some(&doc["items"][1])
+    );
+    assert_eq!(doc.get("7"), None);
+
+    let values = doc["values"].as_typed_array().unwrap();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values.get(2), Some(Value::U16(3)));
+    assert_eq!(values.get(3), None);
+    // Typed arrays are not indexed like generic arrays.
+    assert!(doc["values"][0].is_null());
+}
+
+#[test]
+fn accessors() {
+    assert_eq!(Value::I8(-1).as_i64(), Some(-1));
+    assert_eq!(Value::I8(-1).as_u64(), None);
+    assert_eq!(Value::U64(u64::MAX).as_i64(), None);
+    assert_eq!(Value::U64(u64::MAX).as_u64(), Some(u64::MAX));
+    assert_eq!(Value::I128(-5).as_i64(), Some(-5));
+    assert_eq!(Value::F32(0.5).as_f64(), Some(0.5));
+    assert_eq!(Value::U32(3).as_f64(), Some(3.0));
+    assert_eq!(Value::String("3".into()).as_i64(), None);
+    assert_eq!(Value::Bool(true).as_bool(), Some(true));
+    assert_eq!(Value::Null.as_bool(), None);
+    assert!(Value::Array(vec![]).as_array().unwrap().is_empty());
+    assert!(Value::Null.as_object().is_none());
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(Value::from(3i64), Value::I64(3));
+    assert_eq!(Value::from("s"), Value::String("s".into()));
+    assert_eq!(Value::from(Some(true)), Value::Bool(true));
+    assert_eq!(Value::from(None::<u8>), Value::Null);
+    assert_eq!(
+        Value::from(vec![true, false]),
+        Value::TypedArray(TypedArray::Bool(vec![true, false]))
+    );
+    assert_eq!(
+        Value::from(vec!["a".to_string()]),
+        Value::TypedArray(TypedArray::String(vec!["a".into()]))
+    );
+    assert_eq!(Key::from(4u8), Key::U8(4));
+    assert_eq!(Key::from("k"), Key::String("k".into()));
+}
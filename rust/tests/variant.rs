@@ -0,0 +1,46 @@
+use beve::{Reader, Value, Writer};
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(value).unwrap();
+    writer.into_bytes()
+}
+
+fn variant(index: u64, value: Value) -> Value {
+    Value::Variant {
+        index,
+        value: Box::new(value),
+    }
+}
+
+#[test]
+fn variant_bytes() {
+    let bytes = encode(&variant(2, Value::U8(7)));
+    assert_eq!(bytes, [0b00001110, 2 << 2, 0b00010001, 7]);
+    let read = Reader::new(&bytes).read_value().unwrap();
+    assert_eq!(read.as_variant(), Some((2, &Value::U8(7))));
+}
+
+#[test]
+fn variant_round_trip() {
+    for value in [
+        variant(0, Value::Null),
+        variant(64, Value::String("wide tag".into())),
+        variant(1 << 40, Value::Array(vec![Value::Bool(true)])),
+        Value::Array(vec![
+            variant(1, Value::I32(-1)),
+            variant(3, variant(4, Value::F64(0.5))),
+        ]),
+    ] {
+        assert_eq!(Reader::new(&encode(&value)).read_value().unwrap(), value);
+    }
+    assert_eq!(Value::Null.as_variant(), None);
+}
+
+#[test]
+fn truncated_variant() {
+    let bytes = encode(&variant(1, Value::I32(1)));
+    for len in 1..bytes.len() {
+        assert!(Reader::new(&bytes[..len]).read_value().is_err());
+    }
+}
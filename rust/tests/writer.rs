@@ -0,0 +1,228 @@
+use std::io;
+
+use beve::{Error, TypedArray, Value, Writer};
+
+/// Records the size of every write it is handed.
+#[derive(Default)]
+struct Sink {
+    bytes: Vec<u8>,
+    writes: Vec<usize>,
+}
+
+impl io::Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.extend_from_slice(buf);
+        self.writes.push(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn document() -> Value {
+    Value::Object(vec![
+        ("name".into(), Value::String("doc".into())),
+        (
+            "values".into(),
+            Value::TypedArray(TypedArray::F64((0..5000).map(f64::from).collect())),
+        ),
+        (
+            "flags".into(),
+            Value::TypedArray(TypedArray::Bool(vec![true; 9])),
+        ),
+    ])
+}
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new(0);
+    writer.write_value(value).unwrap();
+    writer.into_bytes()
+}
+
+#[test]
+fn writer_over_io() {
+    let mut sink = Sink::default();
+    let mut writer = Writer::from_io(&mut sink);
+    writer.write_value(&document()).unwrap();
+    let len = writer.finish().unwrap();
+    assert_eq!(sink.bytes, encode(&document()));
+    assert_eq!(len, sink.bytes.len());
+}
+
+#[test]
+fn writer_over_slice() {
+    let expected = encode(&document());
+
+    let mut bytes = vec![0; expected.len()];
+    let mut writer = Writer::from_slice(&mut bytes);
+    writer.write_value(&document()).unwrap();
+    assert_eq!(writer.finish().unwrap(), expected.len());
+    assert_eq!(bytes, expected);
+
+    let mut bytes = vec![0; expected.len() - 1];
+    let mut writer = Writer::from_slice(&mut bytes);
+    writer.write_value(&document()).unwrap();
+    assert_eq!(writer.as_bytes(), &expected[..expected.len() - 1]);
+    let err = writer.finish().unwrap_err();
+    assert!(
+        matches!(err, Error::BufferTooSmall { needed } if needed == expected.len()),
+        "{err:?}"
+    );
+}
+
+#[cfg(feature = "serde")]
+mod serde_output {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u32,
+        values: Vec<f64>,
+        names: Vec<String>,
+    }
+
+    fn record() -> Record {
+        Record {
+            id: 1,
+            values: vec![0.5; 100],
+            names: vec!["a".into(), "bc".into()],
+        }
+    }
+
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Number {
+        Int(i32),
+        Float(f64),
+    }
+
+    fn generic<T: Copy>(values: &[T], value: fn(T) -> Value) -> Vec<u8> {
+        encode(&Value::Array(values.iter().map(|&v| value(v)).collect()))
+    }
+
+    #[test]
+    fn to_writer_writes_generic_arrays() {
+        let mut sink = Sink::default();
+        beve::to_writer(&mut sink, &record()).unwrap();
+        assert_ne!(sink.bytes, beve::to_vec(&record()).unwrap());
+        assert_eq!(beve::from_slice::<Record>(&sink.bytes).unwrap(), record());
+
+        let Value::Object(members) = beve::Reader::new(&sink.bytes).read_value().unwrap() else {
+            panic!("not an object");
+        };
+        assert!(matches!(members[1].1, Value::Array(_)));
+    }
+
+    #[test]
+    fn to_writer_streams_long_sequences() {
+        let values: Vec<f64> = (0..100_000).map(f64::from).collect();
+        let mut sink = Sink::default();
+        beve::to_writer(&mut sink, &values).unwrap();
+        assert_eq!(sink.bytes, generic(&values, Value::F64));
+        assert!(sink.writes.len() > 1);
+        assert!(
+            sink.writes.iter().all(|&n| n <= 8 * 1024),
+            "{:?}",
+            sink.writes
+        );
+
+        let names = vec!["name"; 10_000];
+        let mut sink = Sink::default();
+        beve::to_writer(&mut sink, &names).unwrap();
+        assert_eq!(sink.bytes, generic(&names, |s| Value::String(s.into())));
+        assert!(
+            sink.writes.iter().all(|&n| n <= 8 * 1024),
+            "{:?}",
+            sink.writes
+        );
+    }
+
+    #[test]
+    fn mixed_sequences() {
+        let mixed = vec![Number::Int(1), Number::Int(2), Number::Float(0.5)];
+        let expected = encode(&Value::Array(vec![
+            Value::I32(1),
+            Value::I32(2),
+            Value::F64(0.5),
+        ]));
+        assert_eq!(beve::to_vec(&mixed).unwrap(), expected);
+
+        let mut sink = Sink::default();
+        beve::to_writer(&mut sink, &mixed).unwrap();
+        assert_eq!(sink.bytes, expected);
+
+        let mut bytes = vec![0; expected.len()];
+        assert_eq!(beve::to_slice(&mut bytes, &mixed).unwrap(), expected.len());
+        assert_eq!(bytes, expected);
+
+        // The element of another type comes long after the first 8 KiB were
+        // written out.
+        let mut long: Vec<Number> = (0..10_000).map(Number::Int).collect();
+        long.push(Number::Float(0.5));
+        let mut values: Vec<Value> = (0..10_000).map(Value::I32).collect();
+        values.push(Value::F64(0.5));
+        let expected = encode(&Value::Array(values));
+        assert_eq!(beve::to_vec(&long).unwrap(), expected);
+        let mut sink = Sink::default();
+        beve::to_writer(&mut sink, &long).unwrap();
+        assert_eq!(sink.bytes, expected);
+        assert!(sink.writes.len() > 1);
+    }
+
+    #[test]
+    fn unknown_lengths_are_held() {
+        // Filtering leaves the length unknown.
+        let values = (0..10_000).map(f64::from).filter(|_| true);
+        let mut sink = Sink::default();
+        let mut writer = Writer::from_io(&mut sink);
+        serde::Serializer::collect_seq(&mut beve::Serializer::new(&mut writer), values.clone())
+            .unwrap();
+        writer.finish().unwrap();
+        assert_eq!(sink.writes.len(), 1);
+        assert_eq!(
+            sink.bytes,
+            encode(&Value::TypedArray(TypedArray::F64(values.collect())))
+        );
+    }
+
+    #[test]
+    fn to_slice() {
+        let expected = beve::to_vec(&record()).unwrap();
+
+        let mut bytes = vec![0; expected.len()];
+        assert_eq!(
+            beve::to_slice(&mut bytes, &record()).unwrap(),
+            expected.len()
+        );
+        assert_eq!(bytes, expected);
+
+        let mut bytes = vec![0; 10];
+        let err = beve::to_slice(&mut bytes, &record()).unwrap_err();
+        assert!(
+            matches!(err, Error::BufferTooSmall { needed } if needed == expected.len()),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn to_slice_bool_sequences() {
+        // Booleans take a byte each until the sequence ends and they are
+        // packed, so the slice must hold one byte per element.
+        let flags = vec![true; 20];
+        let expected = beve::to_vec(&flags).unwrap();
+        assert_eq!(expected.len(), 5);
+
+        let mut bytes = vec![0; expected.len()];
+        let err = beve::to_slice(&mut bytes, &flags).unwrap_err();
+        let Error::BufferTooSmall { needed } = err else {
+            panic!("{err:?}");
+        };
+        let mut bytes = vec![0; needed];
+        assert_eq!(beve::to_slice(&mut bytes, &flags).unwrap(), expected.len());
+        assert_eq!(&bytes[..expected.len()], expected);
+    }
+}